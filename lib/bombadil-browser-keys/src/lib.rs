@@ -86,3 +86,105 @@ pub fn key_name(code: u8) -> Option<&'static str> {
         _ => None,
     }
 }
+
+/// Modifier keys held down while pressing a key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub fn is_empty(&self) -> bool {
+        !(self.ctrl || self.alt || self.shift || self.meta)
+    }
+
+    /// The bit field used for modifiers in the Chrome DevTools Protocol's
+    /// input events.
+    pub fn bits(&self) -> i64 {
+        (self.alt as i64)
+            | (self.ctrl as i64) << 1
+            | (self.meta as i64) << 2
+            | (self.shift as i64) << 3
+    }
+
+    /// The key codes of the held modifier keys, in the order they're
+    /// pressed.
+    pub fn codes(&self) -> Vec<u8> {
+        [
+            (self.ctrl, 17),
+            (self.alt, 18),
+            (self.shift, 16),
+            (self.meta, 91),
+        ]
+        .into_iter()
+        .filter_map(|(held, code)| held.then_some(code))
+        .collect()
+    }
+}
+
+/// Describes a key press for humans, like "Ctrl+Z" or "Shift+Tab".
+pub fn chord_name(code: u8, modifiers: Modifiers) -> Option<String> {
+    let key = match key_name(code)? {
+        " " => "Space".to_string(),
+        name if name.len() == 1 => name.to_uppercase(),
+        name => name.to_string(),
+    };
+    let mut parts = vec![];
+    if modifiers.ctrl {
+        parts.push("Ctrl".to_string());
+    }
+    if modifiers.alt {
+        parts.push("Alt".to_string());
+    }
+    if modifiers.shift {
+        parts.push("Shift".to_string());
+    }
+    if modifiers.meta {
+        parts.push("Meta".to_string());
+    }
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_name() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        let ctrl_shift = Modifiers {
+            shift: true,
+            ..ctrl
+        };
+        assert_eq!(chord_name(90, ctrl).as_deref(), Some("Ctrl+Z"));
+        assert_eq!(chord_name(90, ctrl_shift).as_deref(), Some("Ctrl+Shift+Z"));
+        assert_eq!(
+            chord_name(13, Modifiers::default()).as_deref(),
+            Some("Enter")
+        );
+        assert_eq!(
+            chord_name(32, Modifiers::default()).as_deref(),
+            Some("Space")
+        );
+        assert_eq!(chord_name(0, ctrl), None);
+    }
+
+    #[test]
+    fn test_modifier_bits() {
+        let modifiers = Modifiers {
+            ctrl: true,
+            shift: true,
+            ..Modifiers::default()
+        };
+        assert_eq!(modifiers.bits(), 2 | 8);
+        assert_eq!(modifiers.codes(), vec![17, 16]);
+        assert!(Modifiers::default().is_empty());
+    }
+}
@@ -1,14 +1,20 @@
 mod duration;
 mod inspect_server;
 mod render;
+mod stop;
 
 use ::url::Url;
 use anyhow::Result;
 use bombadil::specification::domain::Snapshot;
 use clap::{Args, Parser};
 use std::{
+    collections::BTreeSet,
     path::PathBuf,
     str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
     time::{Duration, SystemTime},
 };
 use tempfile::TempDir;
@@ -16,16 +22,28 @@ use tempfile::TempDir;
 use bombadil::{
     browser::{
         BrowserOptions, DebuggerOptions, Emulation, LaunchOptions,
-        actions::BrowserAction, state::BrowserState,
+        actions::BrowserAction, network_conditions::Throttling,
+        state::BrowserState, storage_state::StorageSeed,
     },
+    corpus::Corpus,
+    exploration::{Exploration, ExplorationStrategy, SharedExploration},
+    graph::StateGraph,
     instrumentation::InstrumentationConfig,
-    runner::{ControlFlow, RunObserver, Runner},
+    runner::{ControlFlow, EpisodeOptions, RunObserver, Runner, RunnerOptions},
+    shrink::shrink,
     specification::{convert::ToSchema, verifier::Specification},
     styled,
-    trace::{PropertyViolation, writer::TraceWriter},
+    trace::{
+        PropertyViolation,
+        reader::{read_trace, trace_file_path},
+        writer::TraceWriter,
+    },
+    violations,
 };
 use bombadil_schema::markup;
 
+use crate::stop::{ExitReason, Progress, StopConditions};
+
 /// Property-based testing for web UIs
 #[derive(Parser)]
 #[command(name = "bombadil", version, about, long_about=None)]
@@ -58,14 +76,69 @@ struct TestSharedOptions {
     /// mode
     #[arg(long, default_value_t = 2.0)]
     device_scale_factor: f64,
+    /// Emulate a mobile device, with touch input and a mobile user agent (the default action
+    /// generators then tap, long-press, swipe and pinch instead of clicking and scrolling)
+    #[arg(long)]
+    mobile: bool,
     /// What types of JavaScript to instrument for coverage tracking.
     /// Comma-separated list of: "files", "inline"
     #[arg(long, default_value = "files,inline", value_parser = parse_instrumentation_config)]
     instrument_javascript: InstrumentationConfig,
+    /// How to pick among the available actions, one of: "coverage" (favour
+    /// actions that have discovered new JavaScript coverage), "random"
+    /// (only use the weights from the specification)
+    #[arg(long, default_value = "coverage", value_parser = parse_exploration_strategy)]
+    exploration: ExplorationStrategy,
+    /// Random generator seed, used to reproduce the action choices of a
+    /// previous test run
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Directory where action sequences reaching new coverage or new states are saved, and from
+    /// which later tests restore coverage and start exploring each episode (created if it doesn't
+    /// exist)
+    #[arg(long)]
+    corpus: Option<PathBuf>,
+    /// Number of actions after which the browser is reset to the origin, with cleared cookies and
+    /// storage, to start a new episode with a fresh evaluation of the specification
+    #[arg(long)]
+    episode_length: Option<u64>,
+    /// Number of episodes after which the test stops
+    #[arg(long)]
+    episodes: Option<u64>,
     /// Maximum time to run the test. Accepts a number with a unit suffix:
     /// s (seconds), m (minutes), h (hours), or d (days). Examples: 30s, 5m, 2h, 1d.
     #[arg(long, value_parser = duration::parse_duration)]
     time_limit: Option<Duration>,
+    /// Maximum number of actions to perform, across all workers
+    #[arg(long)]
+    max_actions: Option<u64>,
+    /// Maximum number of distinct states (by transition hash) to reach, across all workers
+    #[arg(long)]
+    max_distinct_states: Option<u64>,
+    /// Stop when no new coverage has been found for this long. Accepts the same format as
+    /// --time-limit.
+    #[arg(long, value_parser = duration::parse_duration)]
+    coverage_plateau: Option<Duration>,
+    /// Run the page under virtual time, which Bombadil advances by each action's timeout (or by
+    /// the duration of an `AdvanceClock` action), making timer-driven behaviour and bounded
+    /// properties independent of machine load. The time limit and coverage plateau still count
+    /// wall-clock time
+    #[arg(long)]
+    virtual_time: bool,
+    /// Throttle the network for the whole test, using one of the presets "slow-3g", "fast-3g",
+    /// "fast-4g", or a custom LATENCY_MS,DOWNLOAD_KBPS,UPLOAD_KBPS
+    #[arg(long, value_parser = parse_throttling)]
+    throttle_network: Option<Throttling>,
+    /// JSON file with cookies and local storage to set before going to the origin, at the start
+    /// and after every reset, e.g. to explore as a logged-in user (uses the format of Playwright's
+    /// storage state files)
+    #[arg(long, value_parser = parse_storage_state)]
+    storage_state: Option<StorageSeed>,
+    /// Capture the accessibility tree of the active tab in every state, as
+    /// `state.accessibilityTree`, which the accessibility properties in
+    /// `@antithesishq/bombadil/defaults/properties` need
+    #[arg(long)]
+    accessibility_tree: bool,
     /// Comma-separated list of Chrome permissions to grant.
     /// Examples: local-network-access, geolocation, notifications.
     #[arg(
@@ -91,6 +164,10 @@ enum Command {
         /// Disable Chromium sandboxing
         #[arg(long, default_value_t = false)]
         no_sandbox: bool,
+        /// Number of browsers to run in parallel, sharing coverage and the violations count (with
+        /// more than one, each writes its trace to a worker-N directory under the output path)
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        workers: u16,
     },
     /// Run a test with an externally managed browser or Electron app (e.g. `chromium
     /// --remote-debugging-port=9992`)
@@ -105,6 +182,46 @@ enum Command {
         #[arg(long)]
         create_target: bool,
     },
+    /// Replay the actions of a recorded trace with a browser managed by Bombadil, checking
+    /// whether the same properties are violated again
+    Replay {
+        /// Path to trace.jsonl file or directory containing it
+        trace_path: PathBuf,
+        #[clap(flatten)]
+        shared: TestSharedOptions,
+        /// Whether the browser should run in a visible window or not
+        #[arg(long, default_value_t = false)]
+        headless: bool,
+        /// Disable Chromium sandboxing
+        #[arg(long, default_value_t = false)]
+        no_sandbox: bool,
+    },
+    /// Shrink the actions of a recorded trace to a shorter sequence that still violates the same
+    /// property, by replaying subsets of it with a browser managed by Bombadil
+    Shrink {
+        /// Path to trace.jsonl file or directory containing it
+        trace_path: PathBuf,
+        #[clap(flatten)]
+        shared: TestSharedOptions,
+        /// Name of the violated property to preserve (defaults to the first one violated in the
+        /// trace)
+        #[arg(long)]
+        property: Option<String>,
+        /// Whether the browser should run in a visible window or not
+        #[arg(long, default_value_t = false)]
+        headless: bool,
+        /// Disable Chromium sandboxing
+        #[arg(long, default_value_t = false)]
+        no_sandbox: bool,
+    },
+    /// Print the graph of abstract states and transitions between them, built from a trace file
+    Graph {
+        /// Path to trace.jsonl file or directory containing it
+        trace_path: PathBuf,
+        /// Output format, one of: "dot" (Graphviz), "json"
+        #[arg(long, default_value = "dot", value_parser = parse_graph_format)]
+        format: GraphFormat,
+    },
     /// Launch Bombadil Inspect to inspect a trace file
     Inspect {
         /// Path to trace.jsonl file or directory containing it
@@ -118,6 +235,29 @@ enum Command {
     },
 }
 
+/// Where a test gets its actions from.
+#[derive(Clone)]
+enum TestMode {
+    Explore,
+    Replay {
+        actions: Vec<BrowserAction>,
+        recorded_properties: BTreeSet<String>,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+/// A browser to run a test in, with its own output directory.
+struct Worker {
+    output_path: PathBuf,
+    browser_options: BrowserOptions,
+    debugger_options: DebuggerOptions,
+}
+
 #[derive(Clone)]
 struct Origin {
     url: Url,
@@ -176,6 +316,71 @@ fn parse_instrumentation_config(
     })
 }
 
+fn parse_exploration_strategy(
+    s: &str,
+) -> std::result::Result<ExplorationStrategy, String> {
+    match s.trim() {
+        "coverage" => Ok(ExplorationStrategy::CoverageGuided),
+        "random" => Ok(ExplorationStrategy::Random),
+        unknown => Err(format!(
+            "unknown exploration strategy '{}', valid options are: coverage, random",
+            unknown
+        )),
+    }
+}
+
+fn parse_throttling(s: &str) -> std::result::Result<Throttling, String> {
+    if let Some(throttling) = Throttling::preset(s.trim()) {
+        return Ok(throttling);
+    }
+
+    let values = s
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<std::result::Result<Vec<_>, _>>();
+    match values.as_deref() {
+        Ok(&[latency_millis, download_kbps, upload_kbps])
+            if latency_millis >= 0.0
+                && download_kbps > 0.0
+                && upload_kbps > 0.0
+                && [latency_millis, download_kbps, upload_kbps]
+                    .iter()
+                    .all(|value| value.is_finite()) =>
+        {
+            Ok(Throttling {
+                latency_millis,
+                download_kbps,
+                upload_kbps,
+            })
+        }
+        _ => Err(format!(
+            "invalid network throttling '{}', valid options are: {}, or LATENCY_MS,DOWNLOAD_KBPS,UPLOAD_KBPS",
+            s,
+            Throttling::PRESETS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn parse_storage_state(s: &str) -> std::result::Result<StorageSeed, String> {
+    StorageSeed::read(std::path::Path::new(s))
+        .map_err(|error| format!("{:#}", error))
+}
+
+fn parse_graph_format(s: &str) -> std::result::Result<GraphFormat, String> {
+    match s.trim() {
+        "dot" => Ok(GraphFormat::Dot),
+        "json" => Ok(GraphFormat::Json),
+        unknown => Err(format!(
+            "unknown graph format '{}', valid options are: dot, json",
+            unknown
+        )),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let env = env_logger::Env::default().default_filter_or("warn");
@@ -192,64 +397,190 @@ async fn main() -> Result<()> {
             shared,
             headless,
             no_sandbox,
+            workers,
+        } => {
+            let output_path = resolve_output_path(&shared)?;
+            let mut user_data_directories = Vec::with_capacity(workers.into());
+            let mut test_workers = Vec::with_capacity(workers.into());
+            for index in 0..workers {
+                let user_data_directory = TempDir::with_prefix("user_data_")?;
+                let worker_output_path = if workers == 1 {
+                    output_path.clone()
+                } else {
+                    output_path.join(format!("worker-{index}"))
+                };
+                test_workers.push(Worker {
+                    browser_options: browser_options(
+                        &shared,
+                        &worker_output_path,
+                        true,
+                    ),
+                    debugger_options: managed_debugger(
+                        &user_data_directory,
+                        headless,
+                        no_sandbox,
+                    ),
+                    output_path: worker_output_path,
+                });
+                user_data_directories.push(user_data_directory);
+            }
+            test(shared, TestMode::Explore, test_workers).await
+        }
+        Command::TestExternal {
+            shared,
+            remote_debugger,
+            create_target,
+        } => {
+            let output_path = resolve_output_path(&shared)?;
+            let worker = Worker {
+                browser_options: browser_options(
+                    &shared,
+                    &output_path,
+                    create_target,
+                ),
+                debugger_options: DebuggerOptions::External { remote_debugger },
+                output_path,
+            };
+            test(shared, TestMode::Explore, vec![worker]).await
+        }
+        Command::Replay {
+            trace_path,
+            shared,
+            headless,
+            no_sandbox,
         } => {
             let user_data_directory = TempDir::with_prefix("user_data_")?;
             let output_path = resolve_output_path(&shared)?;
-
-            let browser_options = BrowserOptions {
-                create_target: true,
-                emulation: Emulation {
-                    width: shared.width,
-                    height: shared.height,
-                    device_scale_factor: shared.device_scale_factor,
-                },
-                instrumentation: shared.instrument_javascript.clone(),
-                downloads_directory: output_path.join("downloads"),
-                grant_permissions: shared
-                    .chrome_grant_permissions
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
+            if trace_file_path(&trace_path) == output_path.join("trace.jsonl") {
+                anyhow::bail!(
+                    "the replay would write to the trace it replays, use a different --output-path"
+                );
+            }
+            let entries = read_trace(&trace_path).await?;
+            // Each episode starts from the origin, so only the one with the
+            // first violation (or the first one) can be replayed.
+            let episode = entries
+                .iter()
+                .find(|entry| !entry.violations.is_empty())
+                .or(entries.first())
+                .map_or(0, |entry| entry.episode);
+            let entries = episode_entries(&entries, episode);
+            let mode = TestMode::Replay {
+                actions: recorded_actions(&entries),
+                recorded_properties: entries
+                    .iter()
+                    .flat_map(|entry| &entry.violations)
+                    .map(|violation| violation.name.clone())
                     .collect(),
-                extra_headers: shared.headers.iter().cloned().collect(),
             };
-            let debugger_options = DebuggerOptions::Managed {
-                launch_options: LaunchOptions {
+            let worker = Worker {
+                browser_options: browser_options(&shared, &output_path, true),
+                debugger_options: managed_debugger(
+                    &user_data_directory,
                     headless,
-                    user_data_directory: user_data_directory
-                        .path()
-                        .to_path_buf(),
                     no_sandbox,
-                },
+                ),
+                output_path,
             };
-            test(output_path, shared, browser_options, debugger_options).await
+            test(shared, mode, vec![worker]).await
         }
-        Command::TestExternal {
+        Command::Shrink {
+            trace_path,
             shared,
-            remote_debugger,
-            create_target,
+            property,
+            headless,
+            no_sandbox,
         } => {
+            let user_data_directory = TempDir::with_prefix("user_data_")?;
             let output_path = resolve_output_path(&shared)?;
-            let browser_options = BrowserOptions {
-                create_target,
-                emulation: Emulation {
-                    width: shared.width,
-                    height: shared.height,
-                    device_scale_factor: shared.device_scale_factor,
-                },
-                instrumentation: shared.instrument_javascript.clone(),
-                downloads_directory: output_path.join("downloads"),
-                grant_permissions: shared
-                    .chrome_grant_permissions
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect(),
-                extra_headers: shared.headers.iter().cloned().collect(),
+            if trace_file_path(&trace_path) == output_path.join("trace.jsonl") {
+                anyhow::bail!(
+                    "the shrunk trace would overwrite the trace it shrinks, use a different --output-path"
+                );
+            }
+            let entries = read_trace(&trace_path).await?;
+
+            // Only the actions of the episode with the first violation of the
+            // property, up to that violation, are needed to reproduce it.
+            let property = match property {
+                Some(property) => property,
+                None => entries
+                    .iter()
+                    .flat_map(|entry| &entry.violations)
+                    .map(|violation| violation.name.clone())
+                    .next()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("the trace has no property violations")
+                    })?,
             };
-            let debugger_options =
-                DebuggerOptions::External { remote_debugger };
-            test(output_path, shared, browser_options, debugger_options).await
+            let violating_index = entries
+                .iter()
+                .position(|entry| {
+                    entry
+                        .violations
+                        .iter()
+                        .any(|violation| violation.name == property)
+                })
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "property `{}` is not violated in the trace",
+                        property
+                    )
+                })?;
+            let episode_start = entries
+                .iter()
+                .position(|entry| {
+                    entry.episode == entries[violating_index].episode
+                })
+                .unwrap_or(0);
+            let actions =
+                recorded_actions(&entries[episode_start..=violating_index]);
+
+            let seed = shared.seed.unwrap_or_else(rand::random);
+            println!(
+                "Shrinking {} actions violating {}...",
+                actions.len(),
+                styled::maybe_blue(property.clone())
+            );
+            let original_length = actions.len();
+            let actions = shrink(actions, |candidate| {
+                reproduces(
+                    &shared, seed, headless, no_sandbox, &property, candidate,
+                )
+            })
+            .await?;
+            println!(
+                "Shrunk from {} to {} actions, replaying the result...",
+                original_length,
+                actions.len()
+            );
+
+            let mode = TestMode::Replay {
+                actions,
+                recorded_properties: BTreeSet::from([property]),
+            };
+            let worker = Worker {
+                browser_options: browser_options(&shared, &output_path, true),
+                debugger_options: managed_debugger(
+                    &user_data_directory,
+                    headless,
+                    no_sandbox,
+                ),
+                output_path,
+            };
+            test(shared, mode, vec![worker]).await
+        }
+        Command::Graph { trace_path, format } => {
+            let entries = read_trace(&trace_path).await?;
+            let graph = StateGraph::from_trace(&entries);
+            match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&graph.to_schema())?
+                ),
+            }
+            Ok(())
         }
         Command::Inspect {
             trace_path,
@@ -259,21 +590,127 @@ async fn main() -> Result<()> {
     }
 }
 
-fn resolve_output_path(shared_options: &TestSharedOptions) -> Result<PathBuf> {
-    match &shared_options.output_path {
-        Some(path) => Ok(path.clone()),
-        None => Ok(TempDir::with_prefix("bombadil_")?.keep().to_path_buf()),
+fn browser_options(
+    shared_options: &TestSharedOptions,
+    output_path: &std::path::Path,
+    create_target: bool,
+) -> BrowserOptions {
+    BrowserOptions {
+        create_target,
+        emulation: Emulation {
+            width: shared_options.width,
+            height: shared_options.height,
+            device_scale_factor: shared_options.device_scale_factor,
+            mobile: shared_options.mobile,
+        },
+        instrumentation: shared_options.instrument_javascript.clone(),
+        downloads_directory: output_path.join("downloads"),
+        grant_permissions: shared_options
+            .chrome_grant_permissions
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        extra_headers: shared_options.headers.iter().cloned().collect(),
+        virtual_time: shared_options.virtual_time,
+        throttling: shared_options.throttle_network,
+        storage_seed: shared_options.storage_state.clone(),
+        accessibility_tree: shared_options.accessibility_tree,
     }
 }
 
-async fn test(
-    output_path: PathBuf,
-    shared_options: TestSharedOptions,
-    browser_options: BrowserOptions,
-    debugger_options: DebuggerOptions,
-) -> Result<()> {
+/// The actions of the entries, leaving out setup steps, as the runner applies
+/// the specification's setup itself.
+fn recorded_actions(
+    entries: &[bombadil_schema::TraceEntry],
+) -> Vec<BrowserAction> {
+    entries
+        .iter()
+        .filter(|entry| !entry.setup)
+        .filter_map(|entry| entry.action.as_ref())
+        .map(BrowserAction::from_api)
+        .collect()
+}
+
+/// The entries of a single episode, which are contiguous in a trace.
+fn episode_entries(
+    entries: &[bombadil_schema::TraceEntry],
+    episode: u64,
+) -> &[bombadil_schema::TraceEntry] {
+    let start = entries
+        .iter()
+        .position(|entry| entry.episode == episode)
+        .unwrap_or(entries.len());
+    let end = entries[start..]
+        .iter()
+        .position(|entry| entry.episode != episode)
+        .map_or(entries.len(), |length| start + length);
+    &entries[start..end]
+}
+
+/// Replays the actions from the origin, in a fresh browser, and checks
+/// whether the property is violated.
+async fn reproduces(
+    shared_options: &TestSharedOptions,
+    seed: u64,
+    headless: bool,
+    no_sandbox: bool,
+    property: &str,
+    actions: Vec<BrowserAction>,
+) -> Result<bool> {
+    let user_data_directory = TempDir::with_prefix("user_data_")?;
+    let scratch_directory = TempDir::with_prefix("bombadil_shrink_")?;
+    let runner = Runner::new(
+        shared_options.origin.url.clone(),
+        specification(shared_options),
+        RunnerOptions {
+            seed,
+            exploration: shared_options.exploration,
+            episodes: EpisodeOptions::default(),
+        },
+        browser_options(shared_options, scratch_directory.path(), true),
+        managed_debugger(&user_data_directory, headless, no_sandbox),
+    )
+    .await?;
+
+    struct ShrinkObserver<'a> {
+        property: &'a str,
+    }
+
+    impl RunObserver for ShrinkObserver<'_> {
+        type StopValue = bool;
+
+        async fn on_new_state(
+            &mut self,
+            _state: &BrowserState,
+            _last_action: Option<&BrowserAction>,
+            _snapshots: &[Snapshot],
+            violations: &[PropertyViolation],
+        ) -> anyhow::Result<ControlFlow<Self::StopValue>> {
+            if violations
+                .iter()
+                .any(|violation| violation.name == self.property)
+            {
+                return Ok(ControlFlow::Stop(true));
+            }
+            Ok(ControlFlow::Continue)
+        }
+
+        async fn on_interrupted(&mut self) -> anyhow::Result<Self::StopValue> {
+            anyhow::bail!("interrupted while shrinking")
+        }
+    }
+
+    let mut observer = ShrinkObserver { property };
+    Ok(runner
+        .replay(actions, &mut observer)
+        .await?
+        .unwrap_or(false))
+}
+
+fn specification(shared_options: &TestSharedOptions) -> Specification {
     // Load a user-provided specification, or use the defaults provided by Bombadil.
-    let specification = if let Some(path) = &shared_options.specification_file {
+    if let Some(path) = &shared_options.specification_file {
         let path = if path.is_relative() && !path.starts_with(".") {
             PathBuf::from(".").join(path)
         } else {
@@ -288,40 +725,65 @@ async fn test(
         Specification {
             module_specifier: "@antithesishq/bombadil/defaults".to_string(),
         }
-    };
+    }
+}
 
-    let runner = Runner::new(
-        shared_options.origin.url,
-        specification,
-        browser_options,
-        debugger_options,
-    )
-    .await?;
+fn managed_debugger(
+    user_data_directory: &TempDir,
+    headless: bool,
+    no_sandbox: bool,
+) -> DebuggerOptions {
+    DebuggerOptions::Managed {
+        launch_options: LaunchOptions {
+            headless,
+            user_data_directory: user_data_directory.path().to_path_buf(),
+            no_sandbox,
+        },
+    }
+}
+
+fn resolve_output_path(shared_options: &TestSharedOptions) -> Result<PathBuf> {
+    match &shared_options.output_path {
+        Some(path) => Ok(path.clone()),
+        None => Ok(TempDir::with_prefix("bombadil_")?.keep().to_path_buf()),
+    }
+}
+
+async fn test(
+    shared_options: TestSharedOptions,
+    mode: TestMode,
+    workers: Vec<Worker>,
+) -> Result<()> {
+    let seed = shared_options.seed.unwrap_or_else(rand::random);
+    log::info!("using seed {}", seed);
 
     struct MainObserver {
         writer: TraceWriter,
+        /// Prefix for printed actions, set when there are several workers.
+        label: Option<String>,
         exit_on_violation: bool,
         test_start: Option<bombadil_schema::Time>,
         deadline: Option<SystemTime>,
         output_path: PathBuf,
-        violations_count: u64,
-    }
-
-    #[derive(Clone, Copy, Debug)]
-    enum ExitReason {
-        ExitOnViolation,
-        TimeLimit,
-        Interrupted,
-    }
-
-    #[derive(Clone, Copy, Debug)]
-    struct TestResult {
-        exit_reason: ExitReason,
-        violations_count: u64,
+        /// Violations found by all workers.
+        violations_count: Arc<AtomicU64>,
+        violated_properties: BTreeSet<String>,
+        /// The current episode, if the test is split into episodes.
+        episode: Option<u64>,
+        /// Whether the specification's setup is being applied, whose steps
+        /// don't count towards `--max-actions`.
+        in_setup: bool,
+        stop_conditions: StopConditions,
+        /// Actions and states of all workers.
+        progress: Arc<Mutex<Progress>>,
+        exploration: SharedExploration,
+        first_state_time: Option<SystemTime>,
+        /// Set when any worker failed, so that the others stop too.
+        worker_failed: Arc<AtomicBool>,
     }
 
     impl RunObserver for MainObserver {
-        type StopValue = TestResult;
+        type StopValue = ExitReason;
 
         async fn on_new_state(
             &mut self,
@@ -333,27 +795,60 @@ async fn test(
             let test_start = *self.test_start.get_or_insert(
                 bombadil_schema::Time::from_system_time(state.timestamp),
             );
+            // Stop conditions count wall-clock time, as state timestamps are
+            // virtual when running under virtual time.
+            let now = SystemTime::now();
+            let first_state_time = *self.first_state_time.get_or_insert(now);
 
             if let Some(action) = last_action {
-                println!(
-                    "{} {}",
-                    render::format_timestamp(state.timestamp, test_start),
-                    render::format_action(action)
-                );
+                let timestamp =
+                    render::format_timestamp(state.timestamp, test_start);
+                match &self.label {
+                    Some(label) => println!(
+                        "{} {} {}",
+                        styled::maybe_dimmed(label.clone()),
+                        timestamp,
+                        render::format_action(action)
+                    ),
+                    None => {
+                        println!(
+                            "{} {}",
+                            timestamp,
+                            render::format_action(action)
+                        )
+                    }
+                }
             }
 
-            self.violations_count += violations.len() as u64;
+            let violations_count = self
+                .violations_count
+                .fetch_add(violations.len() as u64, Ordering::SeqCst)
+                + violations.len() as u64;
             for violation in violations {
                 log::info!("violation of property `{}`", violation.name);
+                self.violated_properties.insert(violation.name.clone());
                 let api_violation = violation.to_schema();
+                // Only the first occurrence of each distinct violation is
+                // printed, the rest are counted in the summary.
+                if self
+                    .writer
+                    .violations()
+                    .contains(&violations::fingerprint(&api_violation))
+                {
+                    continue;
+                }
                 let markup = markup::render_violation(&api_violation);
                 let text = styled::markup_to_styled(&markup, test_start);
+                let heading = match self.episode {
+                    Some(episode) => format!(
+                        "{} was violated in episode {}:",
+                        violation.name, episode
+                    ),
+                    None => format!("{} was violated:", violation.name),
+                };
                 println!(
                     "\n{}\n\n{}\n",
-                    styled::maybe_red(styled::maybe_bold(format!(
-                        "{} was violated:",
-                        violation.name
-                    ))),
+                    styled::maybe_red(styled::maybe_bold(heading)),
                     text
                 );
             }
@@ -362,31 +857,75 @@ async fn test(
                 .write(state, last_action, snapshots, violations)
                 .await?;
 
-            if self.violations_count > 0 && self.exit_on_violation {
-                return Ok(ControlFlow::Stop(TestResult {
-                    exit_reason: ExitReason::ExitOnViolation,
-                    violations_count: self.violations_count,
-                }));
+            if self.worker_failed.load(Ordering::SeqCst) {
+                log::info!("another worker failed, stopping");
+                return Ok(ControlFlow::Stop(ExitReason::WorkerFailed));
+            }
+
+            if violations_count > 0 && self.exit_on_violation {
+                return Ok(ControlFlow::Stop(ExitReason::ExitOnViolation));
             }
 
             if let Some(deadline) = self.deadline
-                && state.timestamp >= deadline
+                && now >= deadline
             {
                 log::info!("time limit reached, stopping");
-                return Ok(ControlFlow::Stop(TestResult {
-                    exit_reason: ExitReason::TimeLimit,
-                    violations_count: self.violations_count,
-                }));
+                return Ok(ControlFlow::Stop(ExitReason::TimeLimit));
+            }
+
+            let last_discovery = self
+                .exploration
+                .lock()
+                .expect("exploration lock poisoned")
+                .last_discovery()
+                .unwrap_or(first_state_time);
+            let reason = {
+                let mut progress =
+                    self.progress.lock().expect("progress lock poisoned");
+                progress.record(
+                    last_action.is_some() && !self.in_setup,
+                    state.transition_hash,
+                );
+                self.stop_conditions.check(&progress, now, last_discovery)
+            };
+            if let Some(exit_reason) = reason {
+                log::info!("stop condition {:?} met, stopping", exit_reason);
+                return Ok(ControlFlow::Stop(exit_reason));
             }
 
             Ok(ControlFlow::Continue)
         }
 
+        async fn on_new_episode(&mut self, episode: u64) -> anyhow::Result<()> {
+            self.episode = Some(episode);
+            self.in_setup = false;
+            self.writer.start_episode(episode);
+            let heading = format!("episode {episode}");
+            match &self.label {
+                Some(label) => println!(
+                    "{} {}",
+                    styled::maybe_dimmed(label.clone()),
+                    styled::maybe_bold(heading)
+                ),
+                None => println!("{}", styled::maybe_bold(heading)),
+            }
+            Ok(())
+        }
+
+        async fn on_setup_started(&mut self) -> anyhow::Result<()> {
+            self.in_setup = true;
+            self.writer.set_setup(true);
+            Ok(())
+        }
+
+        async fn on_setup_finished(&mut self) -> anyhow::Result<()> {
+            self.in_setup = false;
+            self.writer.set_setup(false);
+            Ok(())
+        }
+
         async fn on_interrupted(&mut self) -> anyhow::Result<Self::StopValue> {
-            Ok(TestResult {
-                exit_reason: ExitReason::Interrupted,
-                violations_count: self.violations_count,
-            })
+            Ok(ExitReason::Interrupted)
         }
     }
 
@@ -399,22 +938,135 @@ async fn test(
 
     let deadline = shared_options.time_limit.map(|d| SystemTime::now() + d);
 
-    let mut observer = MainObserver {
-        writer: TraceWriter::initialize(output_path.clone()).await?,
-        exit_on_violation: shared_options.exit_on_violation,
-        test_start: None,
-        deadline,
-        output_path: output_path.clone(),
-        violations_count: 0,
+    let recorded_properties = match &mode {
+        TestMode::Explore => None,
+        TestMode::Replay {
+            recorded_properties,
+            ..
+        } => Some(recorded_properties.clone()),
     };
 
-    let test_result = runner.run(&mut observer).await?;
+    // Workers share the coverage map and the violations count, but each one
+    // has its own browser, verifier, and trace.
+    let exploration = Exploration::shared(shared_options.exploration);
+    let corpus = match (&mode, &shared_options.corpus) {
+        (TestMode::Explore, Some(directory)) => {
+            Some(Corpus::open(directory.clone()).await?.shared())
+        }
+        _ => None,
+    };
+    let violations_count = Arc::new(AtomicU64::new(0));
+    let progress = Arc::new(Mutex::new(Progress::default()));
+    let worker_failed = Arc::new(AtomicBool::new(false));
+    let workers_count = workers.len();
+    let shared_options = &shared_options;
+    let runs = workers.into_iter().enumerate().map(|(index, worker)| {
+        let mode = mode.clone();
+        let exploration = exploration.clone();
+        let corpus = corpus.clone();
+        let violations_count = violations_count.clone();
+        let progress = progress.clone();
+        let worker_failed = worker_failed.clone();
+        async move {
+            let result = async {
+                let worker_seed = seed.wrapping_add(index as u64);
+                // Before starting the browser, which isn't terminated when this
+                // fails.
+                let writer = TraceWriter::initialize(
+                    worker.output_path.clone(),
+                    &bombadil_schema::TraceMetadata { seed: worker_seed },
+                )
+                .await?;
+                let runner = Runner::new(
+                    shared_options.origin.url.clone(),
+                    specification(shared_options),
+                    RunnerOptions {
+                        seed: worker_seed,
+                        exploration: shared_options.exploration,
+                        episodes: EpisodeOptions {
+                            length: shared_options.episode_length,
+                            count: shared_options.episodes,
+                        },
+                    },
+                    worker.browser_options,
+                    worker.debugger_options,
+                )
+                .await?
+                .with_exploration(exploration.clone());
+                let runner = match corpus {
+                    Some(corpus) => runner.with_corpus(corpus),
+                    None => runner,
+                };
+
+                let mut observer = MainObserver {
+                    writer,
+                    label: (workers_count > 1).then(|| format!("[{index}]")),
+                    exit_on_violation: shared_options.exit_on_violation,
+                    test_start: None,
+                    deadline,
+                    output_path: worker.output_path,
+                    violations_count,
+                    violated_properties: BTreeSet::new(),
+                    episode: (matches!(mode, TestMode::Explore)
+                        && (shared_options.episode_length.is_some()
+                            || shared_options.episodes.is_some()))
+                    .then_some(0),
+                    in_setup: false,
+                    stop_conditions: StopConditions {
+                        max_actions: shared_options.max_actions,
+                        max_distinct_states: shared_options.max_distinct_states,
+                        coverage_plateau: shared_options.coverage_plateau,
+                    },
+                    progress,
+                    exploration,
+                    first_state_time: None,
+                    worker_failed: worker_failed.clone(),
+                };
 
-    let heading = if let Some(TestResult {
-        exit_reason,
-        violations_count,
-    }) = test_result
-    {
+                let test_result = match mode {
+                    TestMode::Explore => runner.run(&mut observer).await,
+                    TestMode::Replay { actions, .. } => {
+                        runner.replay(actions, &mut observer).await
+                    }
+                };
+                // Also written when the run failed, where the graph and the
+                // violations found so far are most useful.
+                let written = async {
+                    observer.writer.write_graph().await?;
+                    observer.writer.write_violations().await
+                }
+                .await;
+                let test_result = test_result?;
+                written?;
+                anyhow::Ok((test_result, observer))
+            }
+            .await;
+            if result.is_err() {
+                worker_failed.store(true, Ordering::SeqCst);
+            }
+            result
+        }
+    });
+    // Every worker is joined, even after one of them failed, so that all of
+    // them stop and terminate their browsers before the error is reported.
+    let finished = futures::future::join_all(runs)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    let violated_properties: BTreeSet<String> = finished
+        .iter()
+        .flat_map(|(_, observer)| observer.violated_properties.iter().cloned())
+        .collect();
+    if let Some(recorded_properties) = &recorded_properties {
+        print_replay_comparison(recorded_properties, &violated_properties);
+    }
+
+    let violations_count = violations_count.load(Ordering::SeqCst);
+    let exit_reason = ExitReason::most_relevant(
+        finished.iter().filter_map(|(result, _)| *result),
+    );
+    let heading = if let Some(exit_reason) = exit_reason {
         let findings = match violations_count {
             0 => "".into(),
             1 => ", finding 1 violation".into(),
@@ -428,9 +1080,25 @@ async fn test(
             ExitReason::TimeLimit => {
                 format!("Test finished after time limit{findings}!")
             }
+            ExitReason::MaxActions => {
+                format!("Test finished after maximum actions{findings}!")
+            }
+            ExitReason::MaxDistinctStates => {
+                format!(
+                    "Test finished after maximum distinct states{findings}!"
+                )
+            }
+            ExitReason::CoveragePlateau => {
+                format!(
+                    "Test finished after coverage stopped growing{findings}!"
+                )
+            }
             ExitReason::Interrupted => {
                 format!("Test was interrupted by SIGINT{findings}!",)
             }
+            ExitReason::WorkerFailed => {
+                format!("Test stopped after a worker failed{findings}!")
+            }
         });
 
         if violations_count > 0 {
@@ -442,19 +1110,89 @@ async fn test(
         styled::maybe_bold("Test finished!".to_string())
     };
 
+    let unique_violations: Vec<String> = finished
+        .iter()
+        .flat_map(|(_, observer)| {
+            observer.writer.violations().unique().iter().map(|unique| {
+                let times = match unique.count {
+                    1 => "once".to_string(),
+                    n => format!("{n} times"),
+                };
+                let line = format!(
+                    "{} violated {} (trace entries {} to {})",
+                    styled::maybe_blue(unique.name.clone()),
+                    times,
+                    unique.first_index,
+                    unique.last_index
+                );
+                match &observer.label {
+                    Some(label) => {
+                        format!(
+                            "{} {}",
+                            styled::maybe_dimmed(label.clone()),
+                            line
+                        )
+                    }
+                    None => line,
+                }
+            })
+        })
+        .collect();
+    let heading = if unique_violations.is_empty() {
+        heading
+    } else {
+        format!(
+            "{heading}\n\nDistinct violations:\n\n  {}",
+            unique_violations.join("\n  ")
+        )
+    };
+
+    let inspect_commands: Vec<String> = finished
+        .iter()
+        .map(|(_, observer)| {
+            styled::maybe_italic(format!(
+                "bombadil inspect {}",
+                observer.output_path.display()
+            ))
+        })
+        .collect();
     println!(
         "\n{heading}\n\nInspect the test results using:\n\n  {}",
-        styled::maybe_italic(format!(
-            "bombadil inspect {}",
-            observer.output_path.display()
-        ))
+        inspect_commands.join("\n  ")
     );
 
-    if let Some(result) = test_result
-        && result.violations_count > 0
-    {
+    if violations_count > 0 {
+        // With several workers, the interleaving of their coverage updates
+        // isn't reproducible, and so neither are their action choices.
+        if recorded_properties.is_none() && workers_count == 1 {
+            println!(
+                "\nReproduce the action choices of this test by adding:\n\n  {}",
+                styled::maybe_italic(format!("--seed {seed}"))
+            );
+        }
         std::process::exit(2);
     }
 
     Ok(())
 }
+
+fn print_replay_comparison(
+    recorded: &BTreeSet<String>,
+    replayed: &BTreeSet<String>,
+) {
+    if recorded.is_empty() && replayed.is_empty() {
+        println!(
+            "\nNo properties were violated, neither in the recorded trace nor in the replay."
+        );
+        return;
+    }
+    println!("\n{}", styled::maybe_bold("Replay results:".to_string()));
+    for name in recorded.union(replayed) {
+        let status = match (recorded.contains(name), replayed.contains(name)) {
+            (true, true) => styled::maybe_red("reproduced".to_string()),
+            (true, false) => styled::maybe_dimmed("not reproduced".to_string()),
+            _ => styled::maybe_red("new violation".to_string()),
+        };
+        println!("  {} {}", styled::maybe_blue(name.clone()), status);
+    }
+}
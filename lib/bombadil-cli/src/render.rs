@@ -1,6 +1,9 @@
-use bombadil_browser_keys::key_name;
+use bombadil_browser_keys::chord_name;
 
-use bombadil::{browser::actions::BrowserAction, styled};
+use bombadil::{
+    browser::actions::{BrowserAction, NetworkFault},
+    styled,
+};
 
 pub fn format_timestamp(
     timestamp: std::time::SystemTime,
@@ -82,8 +85,9 @@ pub fn format_action(action: &BrowserAction) -> String {
                 styled::maybe_blue(format!("{delay_millis}ms"))
             )
         }
-        BrowserAction::PressKey { code } => {
-            let key = key_name(*code).unwrap_or("Unknown");
+        BrowserAction::PressKey { code, modifiers } => {
+            let key = chord_name(*code, modifiers.into())
+                .unwrap_or_else(|| "Unknown".to_string());
             format!(
                 "{} {} (code: {})",
                 styled::maybe_bold("Pressing".to_string()),
@@ -117,5 +121,191 @@ pub fn format_action(action: &BrowserAction) -> String {
                 styled::maybe_blue(format!("{}", files.len()))
             )
         }
+        BrowserAction::SelectOptions { selector, values } => {
+            format!(
+                "{} {} in {}",
+                styled::maybe_bold("Selecting".to_string()),
+                styled::maybe_blue(format!("{:?}", values)),
+                styled::maybe_blue(format!("{:?}", selector))
+            )
+        }
+        BrowserAction::SetInputValue { selector, value } => {
+            format!(
+                "{} {} to {}",
+                styled::maybe_bold("Setting".to_string()),
+                styled::maybe_blue(format!("{:?}", selector)),
+                styled::maybe_blue(format!("{:?}", value))
+            )
+        }
+        BrowserAction::InjectFault { url_pattern, fault } => {
+            let fault = match fault {
+                NetworkFault::Fail => "failure".to_string(),
+                NetworkFault::Status { code } => format!("status {code}"),
+                NetworkFault::Delay { millis } => format!("{millis}ms delay"),
+            };
+            format!(
+                "{} {} in next request to {}",
+                styled::maybe_bold("Injecting".to_string()),
+                styled::maybe_blue(fault),
+                styled::maybe_blue(format!("{:?}", url_pattern))
+            )
+        }
+        BrowserAction::GoOffline => {
+            styled::maybe_bold("Going offline".to_string())
+        }
+        BrowserAction::GoOnline => {
+            styled::maybe_bold("Going online".to_string())
+        }
+        BrowserAction::AdvanceClock { millis } => {
+            format!(
+                "{} by {}",
+                styled::maybe_bold("Advancing clock".to_string()),
+                styled::maybe_blue(format!("{millis}ms"))
+            )
+        }
+        BrowserAction::Resize { width, height } => {
+            format!(
+                "{} {}",
+                styled::maybe_bold("Resizing to".to_string()),
+                styled::maybe_blue(format!("{width}x{height}"))
+            )
+        }
+        BrowserAction::SwitchTab { index } => {
+            format!(
+                "{} {}",
+                styled::maybe_bold("Switching to tab".to_string()),
+                styled::maybe_blue(index.to_string())
+            )
+        }
+        BrowserAction::CloseTab { index } => {
+            format!(
+                "{} {}",
+                styled::maybe_bold("Closing tab".to_string()),
+                styled::maybe_blue(index.to_string())
+            )
+        }
+        BrowserAction::Tap {
+            name,
+            content,
+            point,
+        } => {
+            let content_str = content
+                .as_ref()
+                .map(|c| {
+                    format!(
+                        ", content: {}",
+                        styled::maybe_blue(format!("{:?}", c))
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "{} <{name}> (x: {}, y: {}{})",
+                styled::maybe_bold("Tapping".to_string()),
+                styled::maybe_blue(format!("{:.1}", point.x)),
+                styled::maybe_blue(format!("{:.1}", point.y)),
+                content_str
+            )
+        }
+        BrowserAction::LongPress {
+            name,
+            content,
+            point,
+            duration_millis,
+        } => {
+            let content_str = content
+                .as_ref()
+                .map(|c| {
+                    format!(
+                        ", content: {}",
+                        styled::maybe_blue(format!("{:?}", c))
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "{} <{name}> (x: {}, y: {}, duration: {}{})",
+                styled::maybe_bold("Long-pressing".to_string()),
+                styled::maybe_blue(format!("{:.1}", point.x)),
+                styled::maybe_blue(format!("{:.1}", point.y)),
+                styled::maybe_blue(format!("{duration_millis}ms")),
+                content_str
+            )
+        }
+        BrowserAction::Swipe { start, end } => {
+            format!(
+                "{} (from x: {}, y: {} to x: {}, y: {})",
+                styled::maybe_bold("Swiping".to_string()),
+                styled::maybe_blue(format!("{:.1}", start.x)),
+                styled::maybe_blue(format!("{:.1}", start.y)),
+                styled::maybe_blue(format!("{:.1}", end.x)),
+                styled::maybe_blue(format!("{:.1}", end.y))
+            )
+        }
+        BrowserAction::Hover {
+            name,
+            content,
+            point,
+        } => {
+            let content_str = content
+                .as_ref()
+                .map(|c| {
+                    format!(
+                        ", content: {}",
+                        styled::maybe_blue(format!("{:?}", c))
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "{} <{name}> (x: {}, y: {}{})",
+                styled::maybe_bold("Hovering".to_string()),
+                styled::maybe_blue(format!("{:.1}", point.x)),
+                styled::maybe_blue(format!("{:.1}", point.y)),
+                content_str
+            )
+        }
+        BrowserAction::ContextClick {
+            name,
+            content,
+            point,
+        } => {
+            let content_str = content
+                .as_ref()
+                .map(|c| {
+                    format!(
+                        ", content: {}",
+                        styled::maybe_blue(format!("{:?}", c))
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "{} <{name}> (x: {}, y: {}{})",
+                styled::maybe_bold("Right-clicking".to_string()),
+                styled::maybe_blue(format!("{:.1}", point.x)),
+                styled::maybe_blue(format!("{:.1}", point.y)),
+                content_str
+            )
+        }
+        BrowserAction::Drag { from, to, steps } => {
+            format!(
+                "{} (from x: {}, y: {} to x: {}, y: {}, steps: {})",
+                styled::maybe_bold("Dragging".to_string()),
+                styled::maybe_blue(format!("{:.1}", from.x)),
+                styled::maybe_blue(format!("{:.1}", from.y)),
+                styled::maybe_blue(format!("{:.1}", to.x)),
+                styled::maybe_blue(format!("{:.1}", to.y)),
+                styled::maybe_blue(format!("{steps}"))
+            )
+        }
+        BrowserAction::Pinch { origin, scale } => {
+            format!(
+                "{} (x: {}, y: {}, scale: {})",
+                styled::maybe_bold("Pinching".to_string()),
+                styled::maybe_blue(format!("{:.1}", origin.x)),
+                styled::maybe_blue(format!("{:.1}", origin.y)),
+                styled::maybe_blue(format!("{:.2}", scale))
+            )
+        }
+        BrowserAction::RunSetupScript => {
+            styled::maybe_bold("Running setup script".to_string())
+        }
     }
 }
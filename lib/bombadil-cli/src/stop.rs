@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+/// Why a worker stopped, in order of relevance when reporting on a test
+/// whose workers stopped for different reasons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExitReason {
+    ExitOnViolation,
+    Interrupted,
+    TimeLimit,
+    MaxActions,
+    MaxDistinctStates,
+    CoveragePlateau,
+    WorkerFailed,
+}
+
+impl ExitReason {
+    /// The reason to report for a test, out of those of its workers: a
+    /// violation over an interruption, and either over reaching a limit.
+    pub fn most_relevant(
+        reasons: impl IntoIterator<Item = ExitReason>,
+    ) -> Option<ExitReason> {
+        reasons.into_iter().min()
+    }
+}
+
+/// Work done by all workers, checked against the stop conditions.
+#[derive(Debug, Default)]
+pub struct Progress {
+    actions: u64,
+    states: HashSet<u64>,
+}
+
+impl Progress {
+    /// Counts a state reached by any worker, and the action that led to it,
+    /// if it's one to count.
+    pub fn record(&mut self, action: bool, transition_hash: Option<u64>) {
+        if action {
+            self.actions += 1;
+        }
+        if let Some(hash) = transition_hash {
+            self.states.insert(hash);
+        }
+    }
+}
+
+/// Limits on the work done by all workers, after which the test stops.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StopConditions {
+    pub max_actions: Option<u64>,
+    pub max_distinct_states: Option<u64>,
+    pub coverage_plateau: Option<Duration>,
+}
+
+impl StopConditions {
+    /// The first condition met at the time of a state, given the progress so
+    /// far and when coverage last grew (or the first state, if it never has).
+    pub fn check(
+        &self,
+        progress: &Progress,
+        now: SystemTime,
+        last_discovery: SystemTime,
+    ) -> Option<ExitReason> {
+        if self.max_actions.is_some_and(|max| progress.actions >= max) {
+            return Some(ExitReason::MaxActions);
+        }
+        if self
+            .max_distinct_states
+            .is_some_and(|max| progress.states.len() as u64 >= max)
+        {
+            return Some(ExitReason::MaxDistinctStates);
+        }
+        if self.coverage_plateau.is_some_and(|plateau| {
+            now.duration_since(last_discovery)
+                .is_ok_and(|elapsed| elapsed >= plateau)
+        }) {
+            return Some(ExitReason::CoveragePlateau);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::UNIX_EPOCH;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_most_relevant_exit_reason_prefers_violations_over_limits() {
+        assert_eq!(
+            ExitReason::most_relevant([
+                ExitReason::TimeLimit,
+                ExitReason::ExitOnViolation,
+                ExitReason::WorkerFailed,
+            ]),
+            Some(ExitReason::ExitOnViolation)
+        );
+        assert_eq!(
+            ExitReason::most_relevant([
+                ExitReason::WorkerFailed,
+                ExitReason::MaxActions,
+            ]),
+            Some(ExitReason::MaxActions)
+        );
+        assert_eq!(ExitReason::most_relevant([]), None);
+    }
+
+    #[test]
+    fn test_no_conditions_never_stop() {
+        let mut progress = Progress::default();
+        for hash in 0..100 {
+            progress.record(true, Some(hash));
+        }
+        assert_eq!(
+            StopConditions::default().check(&progress, at(1000), at(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_max_actions_counts_actions_of_all_workers() {
+        let conditions = StopConditions {
+            max_actions: Some(4),
+            ..StopConditions::default()
+        };
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let workers = [progress.clone(), progress.clone()];
+
+        // First states have no action leading to them.
+        for worker in &workers {
+            worker.lock().unwrap().record(false, Some(0));
+        }
+        for worker in &workers {
+            worker.lock().unwrap().record(true, Some(1));
+        }
+        assert_eq!(
+            conditions.check(&progress.lock().unwrap(), at(0), at(0)),
+            None
+        );
+        for worker in &workers {
+            worker.lock().unwrap().record(true, Some(1));
+        }
+        assert_eq!(
+            conditions.check(&progress.lock().unwrap(), at(0), at(0)),
+            Some(ExitReason::MaxActions)
+        );
+    }
+
+    #[test]
+    fn test_max_distinct_states_counts_states_reached_by_any_worker_once() {
+        let conditions = StopConditions {
+            max_distinct_states: Some(3),
+            ..StopConditions::default()
+        };
+        let mut progress = Progress::default();
+        // Both workers reach the same states, and one reaches a state that
+        // couldn't be hashed.
+        for hash in [1, 2, 1, 2] {
+            progress.record(true, Some(hash));
+        }
+        progress.record(true, None);
+        assert_eq!(conditions.check(&progress, at(0), at(0)), None);
+
+        progress.record(true, Some(3));
+        assert_eq!(
+            conditions.check(&progress, at(0), at(0)),
+            Some(ExitReason::MaxDistinctStates)
+        );
+    }
+
+    #[test]
+    fn test_coverage_plateau_counts_from_the_last_discovery() {
+        let conditions = StopConditions {
+            coverage_plateau: Some(Duration::from_secs(10)),
+            ..StopConditions::default()
+        };
+        let progress = Progress::default();
+        assert_eq!(conditions.check(&progress, at(19), at(10)), None);
+        assert_eq!(
+            conditions.check(&progress, at(20), at(10)),
+            Some(ExitReason::CoveragePlateau)
+        );
+        // A discovery by another worker after this state's time.
+        assert_eq!(conditions.check(&progress, at(20), at(30)), None);
+    }
+}
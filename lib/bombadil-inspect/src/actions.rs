@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use bombadil_browser_keys::key_name;
+use bombadil_browser_keys::chord_name;
 use bombadil_schema::{Point, Time, TraceEntry};
 use yew::component;
 use yew::prelude::*;
@@ -127,11 +127,20 @@ fn ActionEntry(props: &HistoryEntryProps) -> Html {
                         ("Delay", delay_millis.to_string()),
                     ]),
                 ),
-                bombadil_schema::BrowserAction::PressKey { code, .. } => (
+                bombadil_schema::BrowserAction::PressKey {
+                    code,
+                    modifiers,
+                } => (
                     html!(
                         <>
                             <span class="action-name">{"Press"}</span>
-                            <span>{key_name(*code).unwrap_or("Unknown")}</span>
+                            <span>
+                                {chord_name(
+                                    *code,
+                                    modifiers.into(),
+                                )
+                                .unwrap_or_else(|| "Unknown".to_string())}
+                            </span>
                         </>
                     ),
                     Some(vec![("Code", code.to_string())]),
@@ -172,6 +181,197 @@ fn ActionEntry(props: &HistoryEntryProps) -> Html {
                         ("Files", format!("{} file(s)", files.len())),
                     ]),
                 ),
+                bombadil_schema::BrowserAction::SelectOptions {
+                    selector,
+                    values,
+                } => (
+                    html!(<span class="action-name">{"Select options"}</span>),
+                    Some(vec![
+                        ("Selector", selector.clone()),
+                        ("Values", format!("{values:?}")),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::SetInputValue {
+                    selector,
+                    value,
+                } => (
+                    html!(<span class="action-name">{"Set value"}</span>),
+                    Some(vec![
+                        ("Selector", selector.clone()),
+                        ("Value", format!("{value:?}")),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::InjectFault {
+                    url_pattern,
+                    fault,
+                } => (
+                    html!(<span class="action-name">{"Inject fault"}</span>),
+                    Some(vec![
+                        ("URL pattern", url_pattern.clone()),
+                        (
+                            "Fault",
+                            match fault {
+                                bombadil_schema::NetworkFault::Fail => {
+                                    "Failure".to_string()
+                                }
+                                bombadil_schema::NetworkFault::Status {
+                                    code,
+                                } => format!("Status {code}"),
+                                bombadil_schema::NetworkFault::Delay {
+                                    millis,
+                                } => format!("{millis}ms delay"),
+                            },
+                        ),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::GoOffline => (
+                    html!(<span class="action-name">{"Go offline"}</span>),
+                    None,
+                ),
+                bombadil_schema::BrowserAction::GoOnline => (
+                    html!(<span class="action-name">{"Go online"}</span>),
+                    None,
+                ),
+                bombadil_schema::BrowserAction::AdvanceClock { millis } => (
+                    html!(<span class="action-name">{"Advance clock"}</span>),
+                    Some(vec![("Duration", format!("{millis}ms"))]),
+                ),
+                bombadil_schema::BrowserAction::Resize { width, height } => (
+                    html!(<span class="action-name">{"Resize"}</span>),
+                    Some(vec![("Size", format!("{width}x{height}"))]),
+                ),
+                bombadil_schema::BrowserAction::SwitchTab { index } => (
+                    html!(<span class="action-name">{"Switch tab"}</span>),
+                    Some(vec![("Tab", index.to_string())]),
+                ),
+                bombadil_schema::BrowserAction::CloseTab { index } => (
+                    html!(<span class="action-name">{"Close tab"}</span>),
+                    Some(vec![("Tab", index.to_string())]),
+                ),
+                bombadil_schema::BrowserAction::Tap {
+                    point,
+                    name,
+                    content,
+                } => (
+                    html!(
+                        <>
+                            <span class="action-name">{"Tap"}</span>
+                            <span class="element-tag">
+                                {"<"}<span class="element-name">{name}</span>{" />"}
+                            </span>
+                        </>
+                    ),
+                    Some(vec![
+                        ("Position", format_point(point)),
+                        (
+                            "Content",
+                            format!(
+                                "{:?}",
+                                content.clone().unwrap_or("".into())
+                            ),
+                        ),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::LongPress {
+                    point,
+                    duration_millis,
+                    name,
+                    content,
+                } => (
+                    html!(
+                        <>
+                            <span class="action-name">{"Long press"}</span>
+                            <span class="element-tag">
+                                {"<"}<span class="element-name">{name}</span>{" />"}
+                            </span>
+                        </>
+                    ),
+                    Some(vec![
+                        ("Position", format_point(point)),
+                        ("Duration", format!("{duration_millis}ms")),
+                        (
+                            "Content",
+                            format!(
+                                "{:?}",
+                                content.clone().unwrap_or("".into())
+                            ),
+                        ),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::Swipe { start, end } => (
+                    html!(<span class="action-name">{"Swipe"}</span>),
+                    Some(vec![
+                        ("From", format_point(start)),
+                        ("To", format_point(end)),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::Hover {
+                    point,
+                    name,
+                    content,
+                } => (
+                    html!(
+                        <>
+                            <span class="action-name">{"Hover"}</span>
+                            <span class="element-tag">
+                                {"<"}<span class="element-name">{name}</span>{" />"}
+                            </span>
+                        </>
+                    ),
+                    Some(vec![
+                        ("Position", format_point(point)),
+                        (
+                            "Content",
+                            format!(
+                                "{:?}",
+                                content.clone().unwrap_or("".into())
+                            ),
+                        ),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::ContextClick {
+                    point,
+                    name,
+                    content,
+                } => (
+                    html!(
+                        <>
+                            <span class="action-name">{"Right-click"}</span>
+                            <span class="element-tag">
+                                {"<"}<span class="element-name">{name}</span>{" />"}
+                            </span>
+                        </>
+                    ),
+                    Some(vec![
+                        ("Position", format_point(point)),
+                        (
+                            "Content",
+                            format!(
+                                "{:?}",
+                                content.clone().unwrap_or("".into())
+                            ),
+                        ),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::Drag { from, to, steps } => (
+                    html!(<span class="action-name">{"Drag"}</span>),
+                    Some(vec![
+                        ("From", format_point(from)),
+                        ("To", format_point(to)),
+                        ("Steps", steps.to_string()),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::Pinch { origin, scale } => (
+                    html!(<span class="action-name">{"Pinch"}</span>),
+                    Some(vec![
+                        ("Origin", format_point(origin)),
+                        ("Scale", format!("{scale:.2}")),
+                    ]),
+                ),
+                bombadil_schema::BrowserAction::RunSetupScript => (
+                    html!(<span class="action-name">{"Run setup script"}</span>),
+                    None,
+                ),
             },
             None => return html! {},
         };
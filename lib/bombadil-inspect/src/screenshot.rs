@@ -61,6 +61,36 @@ pub fn Screenshot(props: &ScreenshotProps) -> Html {
                     let y = point.y * device_scale_factor * transform.scale;
                     let radius = 20.0_f64;
                     let diameter = 2.0 * radius;
+                    let path = props
+                        .action
+                        .as_deref()
+                        .and_then(action_end_point)
+                        .map(|end| {
+                            let end_x =
+                                end.x * device_scale_factor * transform.scale;
+                            let end_y =
+                                end.y * device_scale_factor * transform.scale;
+                            html!(
+                                <>
+                                    <line
+                                        x1={x.to_string()}
+                                        y1={y.to_string()}
+                                        x2={end_x.to_string()}
+                                        y2={end_y.to_string()}
+                                        stroke="var(--color-selected)"
+                                        stroke-width="3"
+                                        stroke-dasharray="6 4"
+                                    />
+                                    <circle
+                                        cx={end_x.to_string()}
+                                        cy={end_y.to_string()}
+                                        r={(radius / 2.0).to_string()}
+                                        fill="var(--color-selected)"
+                                    />
+                                </>
+                            )
+                        })
+                        .unwrap_or_default();
                     html!(
                         <svg class="annotation">
                             <path
@@ -83,6 +113,7 @@ pub fn Screenshot(props: &ScreenshotProps) -> Html {
                                 stroke="var(--color-selected)"
                                 stroke-width="3"
                             />
+                            {path}
                         </svg>
                     )
                 })
@@ -126,9 +157,25 @@ impl ContainTransform {
 fn action_point(action: &BrowserAction) -> Option<&Point> {
     match action {
         BrowserAction::Click { point, .. }
-        | BrowserAction::DoubleClick { point, .. } => Some(point),
+        | BrowserAction::DoubleClick { point, .. }
+        | BrowserAction::Tap { point, .. }
+        | BrowserAction::LongPress { point, .. }
+        | BrowserAction::Hover { point, .. }
+        | BrowserAction::ContextClick { point, .. } => Some(point),
         BrowserAction::ScrollUp { origin, .. }
-        | BrowserAction::ScrollDown { origin, .. } => Some(origin),
+        | BrowserAction::ScrollDown { origin, .. }
+        | BrowserAction::Pinch { origin, .. } => Some(origin),
+        BrowserAction::Swipe { start, .. } => Some(start),
+        BrowserAction::Drag { from, .. } => Some(from),
+        _ => None,
+    }
+}
+
+/// Where the pointer ends up, for actions that move it.
+fn action_end_point(action: &BrowserAction) -> Option<&Point> {
+    match action {
+        BrowserAction::Swipe { end, .. } => Some(end),
+        BrowserAction::Drag { to, .. } => Some(to),
         _ => None,
     }
 }
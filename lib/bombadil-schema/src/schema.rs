@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
@@ -66,9 +67,50 @@ impl Time {
     }
 }
 
+/// Information about a test run as a whole, stored as `metadata.json` next to
+/// the trace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceMetadata {
+    pub seed: u64,
+}
+
+/// The abstract states reached during a test and the transitions between
+/// them, stored as `graph.json` next to the trace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateGraph {
+    pub states: Vec<StateNode>,
+    pub transitions: Vec<StateTransition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateNode {
+    pub hash: u64,
+    /// The URL of the first visit to this state.
+    pub url: String,
+    pub visits: u64,
+    /// How many times an action led from this state to a different one.
+    pub exits: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateTransition {
+    pub from: u64,
+    pub to: u64,
+    pub action: String,
+    pub count: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TraceEntry {
     pub timestamp: Time,
+    /// The episode this entry belongs to, counting from 0. Each episode
+    /// starts over from the origin.
+    #[serde(default)]
+    pub episode: u64,
+    /// Whether the entry's action is a step of the specification's setup,
+    /// which is run again before replaying or shrinking the other actions.
+    #[serde(default)]
+    pub setup: bool,
     pub url: String,
     pub hash_previous: Option<u64>,
     pub hash_current: Option<u64>,
@@ -76,6 +118,12 @@ pub struct TraceEntry {
     pub screenshot: String,
     pub snapshots: Vec<Snapshot>,
     pub violations: Vec<PropertyViolation>,
+    /// Network requests that finished or failed since the previous entry.
+    #[serde(default)]
+    pub network: Vec<NetworkRequest>,
+    /// Cookies and web storage when the entry was captured.
+    #[serde(default)]
+    pub storage: Storage,
     pub resources: Resources,
 }
 
@@ -93,6 +141,42 @@ pub struct Resources {
     pub script_duration: f64,
 }
 
+/// The cookies visible to the page, and the web storage of its origin.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Storage {
+    pub cookies: Vec<Cookie>,
+    pub local_storage: BTreeMap<String, String>,
+    pub session_storage: BTreeMap<String, String>,
+    pub indexed_db_databases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Seconds since the epoch, or `None` for session cookies.
+    pub expires: Option<f64>,
+    pub http_only: bool,
+    pub secure: bool,
+    pub same_site: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkRequest {
+    /// When the request was sent.
+    pub timestamp: Time,
+    pub url: String,
+    pub method: String,
+    pub resource_type: Option<String>,
+    /// The HTTP status code, if a response was received.
+    pub status: Option<u16>,
+    pub duration_millis: f64,
+    /// Why the request failed, if it did.
+    pub failure: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum BrowserAction {
     Back,
@@ -114,6 +198,8 @@ pub enum BrowserAction {
     },
     PressKey {
         code: u8,
+        #[serde(default)]
+        modifiers: KeyModifiers,
     },
     ScrollUp {
         origin: Point,
@@ -129,6 +215,121 @@ pub enum BrowserAction {
         selector: String,
         files: Vec<String>,
     },
+    /// Selects exactly the options with the given values in a `<select>`.
+    SelectOptions {
+        selector: String,
+        values: Vec<String>,
+    },
+    /// Sets the value of an input that can't be typed into, like a range,
+    /// date, color or time input.
+    SetInputValue {
+        selector: String,
+        value: String,
+    },
+    InjectFault {
+        url_pattern: String,
+        fault: NetworkFault,
+    },
+    /// Takes all pages offline, as if the network connection was lost.
+    GoOffline,
+    /// Brings all pages back online.
+    GoOnline,
+    /// Lets the given amount of virtual time pass, or waits that long on the
+    /// wall clock when not running under virtual time.
+    AdvanceClock {
+        millis: u64,
+    },
+    /// Changes the size of the viewport, in CSS pixels.
+    Resize {
+        width: u16,
+        height: u16,
+    },
+    /// Makes the tab with the given index, in the order tabs were opened,
+    /// the one actions are applied to and states are captured from.
+    SwitchTab {
+        index: u32,
+    },
+    /// Closes the tab with the given index. If it was the active one, the
+    /// tab opened before it becomes active.
+    CloseTab {
+        index: u32,
+    },
+    Tap {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    LongPress {
+        name: String,
+        content: Option<String>,
+        point: Point,
+        duration_millis: u64,
+    },
+    /// A single-finger touch moving from `start` to `end`.
+    Swipe {
+        start: Point,
+        end: Point,
+    },
+    /// Two fingers around `origin` moving apart (`scale` above 1) or
+    /// together (`scale` below 1).
+    Pinch {
+        origin: Point,
+        scale: f64,
+    },
+    /// Moves the mouse over an element without clicking.
+    Hover {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    /// Clicks with the right mouse button, opening context menus.
+    ContextClick {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    /// Presses the mouse at `from`, moves it to `to` in `steps` moves, and
+    /// releases it, performing an HTML5 drag and drop if one starts.
+    Drag {
+        from: Point,
+        to: Point,
+        steps: u32,
+    },
+    /// Runs the async setup script exported by the specification in the
+    /// page.
+    RunSetupScript,
+}
+
+/// Modifier keys held down during a key press.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct KeyModifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+impl From<&KeyModifiers> for bombadil_browser_keys::Modifiers {
+    fn from(modifiers: &KeyModifiers) -> Self {
+        bombadil_browser_keys::Modifiers {
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            meta: modifiers.meta,
+        }
+    }
+}
+
+/// A fault injected in the first request matching a URL pattern.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum NetworkFault {
+    /// The request fails with a network error.
+    Fail,
+    /// The request gets an empty response with the given status code.
+    Status { code: u16 },
+    /// The request is sent after a delay.
+    Delay { millis: u64 },
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -151,6 +352,23 @@ pub struct PropertyViolation {
     pub violation: Violation,
 }
 
+/// A distinct violation found during a test, identified by a fingerprint of
+/// the property name and the shape of the violation, stored in
+/// `violations.json` next to the trace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UniqueViolation {
+    pub fingerprint: String,
+    pub name: String,
+    /// How many times the violation occurred.
+    pub count: u64,
+    /// Index of the trace entry where the violation first occurred.
+    pub first_index: u64,
+    /// Index of the trace entry where the violation last occurred.
+    pub last_index: u64,
+    /// The first occurrence of the violation.
+    pub violation: Violation,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Violation {
     False {
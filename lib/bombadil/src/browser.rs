@@ -5,19 +5,21 @@ use chromiumoxide::cdp::browser_protocol::network;
 use chromiumoxide::cdp::browser_protocol::page::{
     self, ClientNavigationReason, FrameId, NavigationType,
 };
+use chromiumoxide::cdp::browser_protocol::storage;
 use chromiumoxide::cdp::browser_protocol::target::{self, TargetId};
 use chromiumoxide::cdp::browser_protocol::{dom, emulation};
 use chromiumoxide::cdp::js_protocol::debugger::{self, CallFrameId};
 use chromiumoxide::cdp::js_protocol::runtime::{self};
 use chromiumoxide::page::ScreenshotParams;
 use chromiumoxide::{BrowserConfig, Page};
-use futures::{StreamExt, stream};
+use futures::{StreamExt, future, stream};
 use log;
 use serde_json as json;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::{Duration, UNIX_EPOCH};
 use tempfile::TempDir;
 use tokio::sync::broadcast::error::RecvError;
@@ -28,30 +30,53 @@ use tokio::{select, spawn};
 use tokio_stream::wrappers::BroadcastStream;
 use url::Url;
 
+use crate::browser::accessibility::capture_accessibility_tree;
 use crate::browser::actions::BrowserAction;
+use crate::browser::instrumentation::NetworkFaults;
+use crate::browser::network_conditions::{NetworkConditions, Throttling};
 use crate::browser::state::{
-    BrowserState, CallFrame, ConsoleEntry, Exception, Screenshot,
-    ScreenshotFormat,
+    AccessibilityNode, BrowserState, CallFrame, Connectivity, ConsoleEntry,
+    Exception, NetworkRequest, Screenshot, ScreenshotFormat, Storage, Tab,
 };
+use crate::browser::storage_state::{StorageSeed, capture_storage};
+use crate::browser::tabs::Tabs;
+use crate::browser::virtual_time::VirtualClock;
 
+pub mod accessibility;
 pub mod actions;
 pub mod evaluation;
 pub mod instrumentation;
+pub mod network_conditions;
 pub mod state;
+pub mod storage_state;
+pub mod tabs;
+pub mod virtual_time;
 
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum BrowserEvent {
     StateChanged(BrowserState),
+    /// An action threw or timed out. The browser carries on, capturing the
+    /// next state as usual.
+    ActionFailed(BrowserAction, Arc<anyhow::Error>),
     Error(Arc<anyhow::Error>),
 }
 
+/// What's gathered for the next state while running, and right before and
+/// after pausing.
 #[derive(Debug, Default)]
-struct InnerStateShared {
+pub(crate) struct InnerStateShared {
     generation: Generation,
     console_entries: Vec<ConsoleEntry>,
+    network_requests: Vec<NetworkRequest>,
     exceptions: Vec<Exception>,
     screenshot: Option<Screenshot>,
+    storage: Storage,
+    accessibility_tree: Option<Vec<AccessibilityNode>>,
+    tabs: Vec<Tab>,
+    connectivity: Connectivity,
+    touch: bool,
+    applied_actions: u64,
 }
 
 #[derive(Debug)]
@@ -65,6 +90,7 @@ enum InnerStateKind {
     Pausing,
     Paused,
     Resuming(BrowserAction, Timeout),
+    Resetting,
     Navigating { url: String },
     Loading,
     Running,
@@ -93,10 +119,13 @@ enum InnerEvent {
         url: String,
     },
     TargetDestroyed(TargetId),
+    TabOpened(Arc<Page>, FrameId),
     ConsoleEntry(ConsoleEntry),
+    NetworkRequest(NetworkRequest),
     ActionAccepted(BrowserAction, Timeout),
     ActionApplied(Generation),
     ExceptionThrown(Exception),
+    ResetRequested,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -107,6 +136,7 @@ enum StateRequestReason {
     BackForwardCacheRestore,
     FileDownload,
     Watchdog,
+    TabChanged,
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
@@ -126,15 +156,64 @@ impl std::fmt::Display for Generation {
 
 type Timeout = Duration;
 
+type InnerEventStream = Pin<Box<dyn stream::Stream<Item = InnerEvent> + Send>>;
+
+/// Keeps track of elements with hover and context menu listeners, which
+/// can't be found from the DOM alone, for the default action generators to
+/// target through `window.__bombadil_listeners__.elements(type)`.
+const EVENT_LISTENERS_SCRIPT: &str = r#"
+(() => {
+  const tracked = ["contextmenu", "mouseenter", "mouseover"];
+  const seen = Object.fromEntries(tracked.map((type) => [type, new WeakSet()]));
+  const registered = [];
+  const addEventListener = EventTarget.prototype.addEventListener;
+  EventTarget.prototype.addEventListener = function (type, listener, options) {
+    if (this instanceof Element && seen[type] && !seen[type].has(this)) {
+      seen[type].add(this);
+      registered.push({ type, element: new WeakRef(this) });
+    }
+    return addEventListener.call(this, type, listener, options);
+  };
+  Object.defineProperty(window, "__bombadil_listeners__", {
+    value: {
+      elements: (type) =>
+        registered
+          .filter((entry) => entry.type === type)
+          .map((entry) => entry.element.deref())
+          .filter((element) => element && element.isConnected),
+    },
+  });
+})();
+"#;
+
+/// User agent reported when emulating a mobile device.
+const MOBILE_USER_AGENT: &str = "Mozilla/5.0 (Linux; Android 10; K) \
+    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/139.0.0.0 Mobile \
+    Safari/537.36";
+
+/// Number of simultaneous touch points supported when emulating a mobile
+/// device.
+const MAX_TOUCH_POINTS: i64 = 5;
+
+/// Wall-clock time allowed for advancing virtual time after an action, on
+/// top of the action's timeout.
+const VIRTUAL_TIME_GRACE: Duration = Duration::from_secs(15);
+
 struct BrowserContext {
     sender: Sender<BrowserEvent>,
     actions_sender: Sender<(BrowserAction, Timeout)>,
     inner_events_sender: Sender<InnerEvent>,
     shutdown_receiver: oneshot::Receiver<()>,
-    page: Arc<Page>,
-    frame_id: FrameId,
-    #[allow(unused, reason = "this is going into the scripts soon")]
+    tabs: Tabs,
     origin: Url,
+    emulation: Emulation,
+    faults: NetworkFaults,
+    network_conditions: NetworkConditions,
+    clock: Option<VirtualClock>,
+    storage_seed: Option<StorageSeed>,
+    accessibility_tree: bool,
+    /// How many actions have finished applying, successfully or not.
+    applied_actions: Arc<AtomicU64>,
 }
 
 #[derive(Clone)]
@@ -149,6 +228,35 @@ pub struct Emulation {
     pub width: u16,
     pub height: u16,
     pub device_scale_factor: f64,
+    /// Emulate a mobile device: touch input instead of a mouse, and a mobile
+    /// user agent and viewport.
+    pub mobile: bool,
+}
+
+impl Emulation {
+    /// Emulates the device with the given viewport size, in CSS pixels.
+    pub async fn set_viewport(
+        &self,
+        page: &Page,
+        width: u16,
+        height: u16,
+    ) -> Result<()> {
+        page.execute(
+            emulation::SetDeviceMetricsOverrideParams::builder()
+                .width(width)
+                .height(height)
+                .device_scale_factor(self.device_scale_factor)
+                .mobile(self.mobile)
+                .scale(1)
+                .build()
+                .map_err(|err| {
+                    anyhow!(err)
+                        .context("build SetDeviceMetricsOverrideParams failed")
+                })?,
+        )
+        .await?;
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -159,6 +267,16 @@ pub struct BrowserOptions {
     pub downloads_directory: PathBuf,
     pub grant_permissions: Vec<String>,
     pub extra_headers: HashMap<String, String>,
+    /// Run the page under virtual time, advanced by Bombadil after each
+    /// action rather than by the wall clock.
+    pub virtual_time: bool,
+    /// Throttle the network of all pages, for the whole test.
+    pub throttling: Option<Throttling>,
+    /// Cookies and local storage to set before going to the origin, at the
+    /// start and after every reset.
+    pub storage_seed: Option<StorageSeed>,
+    /// Capture the accessibility tree of the active page in every state.
+    pub accessibility_tree: bool,
 }
 
 #[derive(Clone)]
@@ -173,10 +291,13 @@ pub struct Browser {
     actions_sender: Sender<(BrowserAction, Timeout)>,
     shutdown_sender: Option<oneshot::Sender<()>>,
     done_receiver: Option<oneshot::Receiver<()>>,
-    browser: Option<chromiumoxide::Browser>,
-    page: Arc<Page>,
+    browser: Option<Arc<tokio::sync::Mutex<chromiumoxide::Browser>>>,
+    tabs: Tabs,
+    scripts: Arc<Mutex<Vec<String>>>,
     origin: Url,
     go_to_origin_on_init: bool,
+    storage_seed: Option<StorageSeed>,
+    virtual_time: bool,
 }
 
 impl Drop for Browser {
@@ -198,6 +319,8 @@ impl Browser {
         browser_options: BrowserOptions,
         debugger_options: DebuggerOptions,
     ) -> Result<Self> {
+        let launched =
+            matches!(debugger_options, DebuggerOptions::Managed { .. });
         let (mut browser, mut handler) = match debugger_options {
             DebuggerOptions::External {
                 ref remote_debugger,
@@ -232,67 +355,23 @@ impl Browser {
             Arc::new(find_page(&mut browser).await?)
         };
 
-        page.enable_dom().await?;
-        page.enable_css().await?;
-        page.enable_runtime().await?;
-        page.enable_debugger().await?;
-
-        if !browser_options.extra_headers.is_empty() {
-            page.execute(network::SetExtraHttpHeadersParams::new(
-                network::Headers::new(json::to_value(
-                    &browser_options.extra_headers,
-                )?),
-            ))
-            .await?;
-        }
-
-        // Prevent file downloads to avoid getting stuck
-        page.execute(
-            browser::SetDownloadBehaviorParams::builder()
-                .behavior(browser::SetDownloadBehaviorBehavior::AllowAndName)
-                .events_enabled(true)
-                .download_path(
-                    browser_options.downloads_directory.to_string_lossy(),
-                )
-                .build()
-                .map_err(|s| {
-                    anyhow!(s).context("build SetDownloadBehaviorParams failed")
-                })?,
-        )
-        .await?;
+        let faults = NetworkFaults::new();
+        let network_conditions =
+            NetworkConditions::new(browser_options.throttling);
+        let scripts = Arc::new(Mutex::new(Vec::new()));
+        let mut setup = PageSetup {
+            options: browser_options.clone(),
+            faults: faults.clone(),
+            network_conditions: network_conditions.clone(),
+            clock: None,
+            scripts: scripts.clone(),
+        };
+        setup.apply(&page).await?;
 
-        for permission in &browser_options.grant_permissions {
-            page.execute(
-                browser::SetPermissionParams::builder()
-                    .permission(browser::PermissionDescriptor::new(permission))
-                    .setting(browser::PermissionSetting::Granted)
-                    .build()
-                    .map_err(|s| {
-                        anyhow!(s).context("build SetPermissionParams failed")
-                    })?,
-            )
-            .await?;
+        if browser_options.virtual_time {
+            setup.clock = Some(VirtualClock::start(&page).await?);
         }
 
-        page.execute(
-            emulation::SetDeviceMetricsOverrideParams::builder()
-                .width(browser_options.emulation.width)
-                .height(browser_options.emulation.height)
-                .device_scale_factor(
-                    browser_options.emulation.device_scale_factor,
-                )
-                .mobile(false)
-                .scale(1)
-                .build()
-                .map_err(|err| {
-                    anyhow!(err)
-                        .context("build SetDeviceMetricsOverrideParams failed")
-                })?,
-        )
-        .await?;
-
-        auto_accept_dialogs(page.clone()).await?;
-
         let (inner_events_sender, inner_events_receiver) =
             channel::<InnerEvent>(1024);
 
@@ -303,31 +382,79 @@ impl Browser {
             .mainframe()
             .await?
             .ok_or(anyhow!("no main frame available"))?;
+        let tabs = Tabs::new(page.clone(), frame_id);
 
         let context = BrowserContext {
             sender,
             actions_sender: actions_sender.clone(),
             inner_events_sender: inner_events_sender.clone(),
             shutdown_receiver,
-            page: page.clone(),
-            frame_id,
+            tabs: tabs.clone(),
             origin: origin.clone(),
+            emulation: browser_options.emulation.clone(),
+            faults,
+            network_conditions,
+            clock: setup.clock.clone(),
+            storage_seed: browser_options.storage_seed.clone(),
+            accessibility_tree: browser_options.accessibility_tree,
+            applied_actions: Arc::new(AtomicU64::new(0)),
         };
 
-        instrumentation::instrument_js_coverage(
-            page.clone(),
-            browser_options.instrumentation.clone(),
-        )
-        .await?;
+        forward_page_events(&page, &tabs, inner_events_sender.clone()).await?;
 
         let browser_events = browser
             .event_listener::<target::EventTargetDestroyed>()
             .await?
             .map(|event| InnerEvent::TargetDestroyed(event.target_id.clone()));
 
+        let targets_created = browser
+            .event_listener::<target::EventTargetCreated>()
+            .await?;
+        // New tabs wait before loading anything until they're followed, so
+        // that even their first document runs set up. Only in browsers
+        // launched for the test, as this holds up every new tab, including
+        // those of other clients of an external browser.
+        if launched {
+            browser
+                .execute(
+                    target::SetAutoAttachParams::builder()
+                        .auto_attach(true)
+                        .wait_for_debugger_on_start(true)
+                        .flatten(true)
+                        .filter(target::TargetFilter::new(vec![
+                            target::FilterEntry::builder()
+                                .r#type("page")
+                                .exclude(false)
+                                .build(),
+                            target::FilterEntry::builder()
+                                .exclude(true)
+                                .build(),
+                        ]))
+                        .build()
+                        .map_err(|err| {
+                            anyhow!(err)
+                                .context("build SetAutoAttachParams failed")
+                        })?,
+                )
+                .await?;
+        }
+        let browser = Arc::new(tokio::sync::Mutex::new(browser));
+        follow_new_tabs(
+            Arc::downgrade(&browser),
+            targets_created,
+            setup,
+            tabs.clone(),
+            inner_events_sender.clone(),
+        );
+
+        let events_action_accepted = receiver_to_stream(
+            actions_sender.subscribe(),
+        )
+        .map(|(action, timeout)| InnerEvent::ActionAccepted(action, timeout));
+
         let events_all = stream::select_all(vec![
-            inner_events(&context).await?,
-            Box::pin(browser_events),
+            Box::pin(browser_events) as InnerEventStream,
+            Box::pin(events_action_accepted),
             receiver_to_stream(inner_events_receiver),
         ]);
         run_state_machine(context, events_all, done_sender);
@@ -339,21 +466,32 @@ impl Browser {
             actions_sender,
             shutdown_sender: Some(shutdown_sender),
             done_receiver: Some(done_receiver),
-            page,
+            tabs,
+            scripts,
             origin,
             go_to_origin_on_init: browser_options.create_target,
+            storage_seed: browser_options.storage_seed,
+            virtual_time: browser_options.virtual_time,
         })
     }
 
     pub async fn initiate(&mut self) -> Result<()> {
         if self.go_to_origin_on_init {
-            let page = self.page.clone();
-            let origin = self.origin.to_string();
-            spawn(async move {
-                log::info!("going to origin");
-                let _ = page.goto(origin).await;
-            });
+            spawn(go_to_origin(
+                self.tabs.active(),
+                self.origin.clone(),
+                self.storage_seed.clone(),
+            ));
         } else {
+            // The page is already loaded, so local storage is set in it
+            // directly.
+            if let Some(seed) = &self.storage_seed {
+                let page = self.tabs.active();
+                seed.set_cookies(&page, &self.origin).await?;
+                if let Some(script) = seed.local_storage_script()? {
+                    page.evaluate(script).await?;
+                }
+            }
             let _ = self.inner_events_sender.send(InnerEvent::StateRequested(
                 StateRequestReason::Start,
                 Generation::default(),
@@ -377,7 +515,8 @@ impl Browser {
         // unblocking the state machine so it can exit. Without this ordering,
         // terminate() could deadlock: the state machine waits for a CDP response
         // and the browser never closes because we're waiting for the state machine.
-        if let Some(mut browser) = self.browser.take() {
+        if let Some(browser) = self.browser.take() {
+            let mut browser = browser.lock().await;
             if let Err(error) = browser.close().await {
                 log::warn!("browser close error: {:?}", error);
             }
@@ -409,33 +548,324 @@ impl Browser {
         action: BrowserAction,
         timeout: Timeout,
     ) -> Result<()> {
+        // Without virtual time, there's no clock to advance, and the action
+        // would only wait for as long as it says.
+        if matches!(action, BrowserAction::AdvanceClock { .. })
+            && !self.virtual_time
+        {
+            bail!("AdvanceClock actions require virtual time");
+        }
         self.actions_sender.send((action, timeout))?;
         Ok(())
     }
 
+    /// Clears cookies and storage for the origin, and navigates back to it.
+    /// Must be called when waiting for an action, i.e. after a new state.
+    pub fn reset(&mut self) -> Result<()> {
+        self.inner_events_sender.send(InnerEvent::ResetRequested)?;
+        Ok(())
+    }
+
+    /// Evaluates the script in all open pages, and in every new document
+    /// and tab from now on.
     pub async fn ensure_script_evaluated(&self, script: &str) -> Result<()> {
-        let _ = self.page.evaluate_on_new_document(script).await?;
+        self.scripts
+            .lock()
+            .expect("scripts lock poisoned")
+            .push(script.to_string());
+        for page in self.tabs.pages() {
+            evaluate_script(&page, script).await?;
+        }
+        Ok(())
+    }
+}
 
-        let main_execution_context_id = self
-            .page
-            .execution_context()
-            .await?
-            .ok_or(anyhow!("no execution context available"))?;
-        let _ = self
-            .page
-            .execute(
-                runtime::EvaluateParams::builder()
-                    .expression(script)
-                    .context_id(main_execution_context_id)
-                    .await_promise(true)
+/// Seeds cookies and local storage, if given, and goes to the origin. Local
+/// storage is set by a script that only runs in documents loaded during this
+/// navigation, so the page can't tell it apart from storage it set itself.
+async fn go_to_origin(page: Arc<Page>, origin: Url, seed: Option<StorageSeed>) {
+    let script = match &seed {
+        Some(seed) => match seed.apply(&page, &origin).await {
+            Ok(script) => script,
+            Err(error) => {
+                log::error!("failed to seed storage: {:?}", error);
+                None
+            }
+        },
+        None => None,
+    };
+
+    log::info!("going to origin");
+    let _ = page.goto(origin.to_string()).await;
+
+    if let Some(identifier) = script
+        && let Err(error) = page
+            .execute(page::RemoveScriptToEvaluateOnNewDocumentParams::new(
+                identifier,
+            ))
+            .await
+    {
+        log::warn!("failed to remove storage seeding script: {}", error);
+    }
+}
+
+async fn evaluate_script(page: &Page, script: &str) -> Result<()> {
+    let _ = page.evaluate_on_new_document(script).await?;
+
+    // A new tab waiting to start has no document yet, and runs the script in
+    // its first one.
+    let Some(main_execution_context_id) = page.execution_context().await?
+    else {
+        return Ok(());
+    };
+    let _ = page
+        .execute(
+            runtime::EvaluateParams::builder()
+                .expression(script)
+                .context_id(main_execution_context_id)
+                .await_promise(true)
+                .build()
+                .expect("failed to build EvaluateParams"),
+        )
+        .await;
+    Ok(())
+}
+
+/// Everything needed to set up a page for testing, kept around for setting
+/// up tabs opened during the test like the first one.
+#[derive(Clone)]
+struct PageSetup {
+    options: BrowserOptions,
+    faults: NetworkFaults,
+    network_conditions: NetworkConditions,
+    clock: Option<VirtualClock>,
+    scripts: Arc<Mutex<Vec<String>>>,
+}
+
+impl PageSetup {
+    async fn apply(&self, page: &Arc<Page>) -> Result<()> {
+        let browser_options = &self.options;
+
+        page.enable_dom().await?;
+        page.enable_css().await?;
+        page.enable_runtime().await?;
+        page.enable_debugger().await?;
+        page.execute(network::EnableParams::default()).await?;
+
+        if !browser_options.extra_headers.is_empty() {
+            page.execute(network::SetExtraHttpHeadersParams::new(
+                network::Headers::new(json::to_value(
+                    &browser_options.extra_headers,
+                )?),
+            ))
+            .await?;
+        }
+
+        // Prevent file downloads to avoid getting stuck
+        page.execute(
+            browser::SetDownloadBehaviorParams::builder()
+                .behavior(browser::SetDownloadBehaviorBehavior::AllowAndName)
+                .events_enabled(true)
+                .download_path(
+                    browser_options.downloads_directory.to_string_lossy(),
+                )
+                .build()
+                .map_err(|s| {
+                    anyhow!(s).context("build SetDownloadBehaviorParams failed")
+                })?,
+        )
+        .await?;
+
+        for permission in &browser_options.grant_permissions {
+            page.execute(
+                browser::SetPermissionParams::builder()
+                    .permission(browser::PermissionDescriptor::new(permission))
+                    .setting(browser::PermissionSetting::Granted)
                     .build()
-                    .expect("failed to build EvaluateParams"),
+                    .map_err(|s| {
+                        anyhow!(s).context("build SetPermissionParams failed")
+                    })?,
             )
-            .await;
+            .await?;
+        }
+
+        let emulation = &browser_options.emulation;
+        emulation
+            .set_viewport(page, emulation.width, emulation.height)
+            .await?;
+
+        if browser_options.emulation.mobile {
+            page.execute(
+                emulation::SetTouchEmulationEnabledParams::builder()
+                    .enabled(true)
+                    .max_touch_points(MAX_TOUCH_POINTS)
+                    .build()
+                    .map_err(|err| {
+                        anyhow!(err).context(
+                            "build SetTouchEmulationEnabledParams failed",
+                        )
+                    })?,
+            )
+            .await?;
+            page.execute(emulation::SetUserAgentOverrideParams::new(
+                MOBILE_USER_AGENT,
+            ))
+            .await?;
+        }
+
+        self.network_conditions.attach(page).await?;
+
+        if let Some(clock) = &self.clock {
+            clock.attach(page).await?;
+        }
+
+        page.execute(page::AddScriptToEvaluateOnNewDocumentParams::new(
+            EVENT_LISTENERS_SCRIPT,
+        ))
+        .await?;
+
+        auto_accept_dialogs(page.clone()).await?;
+
+        instrumentation::instrument_js_coverage(
+            page.clone(),
+            browser_options.instrumentation.clone(),
+            self.faults.clone(),
+        )
+        .await?;
+
+        let scripts =
+            self.scripts.lock().expect("scripts lock poisoned").clone();
+        for script in scripts {
+            evaluate_script(page, &script).await?;
+        }
+
         Ok(())
     }
 }
 
+/// Follows pages opened during the test, like popups and links opened in new
+/// tabs, setting them up like the first page.
+fn follow_new_tabs(
+    browser: Weak<tokio::sync::Mutex<chromiumoxide::Browser>>,
+    mut targets_created: impl stream::Stream<Item = Arc<target::EventTargetCreated>>
+    + Send
+    + Unpin
+    + 'static,
+    setup: PageSetup,
+    tabs: Tabs,
+    sender: Sender<InnerEvent>,
+) {
+    spawn(async move {
+        while let Some(event) = targets_created.next().await {
+            let info = &event.target_info;
+            let tracked = tabs
+                .pages()
+                .iter()
+                .any(|page| *page.target_id() == info.target_id);
+            if info.r#type != "page" || tracked {
+                continue;
+            }
+            let Some(browser) = browser.upgrade() else {
+                break;
+            };
+            log::info!("following new tab: {}", info.url);
+            if let Err(error) = open_tab(
+                &browser,
+                info.target_id.clone(),
+                &setup,
+                &tabs,
+                &sender,
+            )
+            .await
+            {
+                log::warn!(
+                    "failed to follow new tab {}: {:?}",
+                    info.url,
+                    error
+                );
+            }
+        }
+    });
+}
+
+async fn open_tab(
+    browser: &tokio::sync::Mutex<chromiumoxide::Browser>,
+    target_id: TargetId,
+    setup: &PageSetup,
+    tabs: &Tabs,
+    sender: &Sender<InnerEvent>,
+) -> Result<()> {
+    let mut page = None;
+    for attempt in 1..=10 {
+        // Not holding the lock while waiting, which would keep the browser
+        // from terminating.
+        let found = {
+            let browser = browser.lock().await;
+            browser.get_page(target_id.clone()).await
+        };
+        if let Ok(found) = found {
+            page = Some(Arc::new(found));
+            break;
+        }
+        sleep(Duration::from_millis(50 * attempt)).await;
+    }
+    let Some(page) = page else {
+        // The target still waits to start, and detaching from it, without a
+        // page to run it from, lets it.
+        let detached = browser
+            .lock()
+            .await
+            .execute(
+                target::DetachFromTargetParams::builder()
+                    .target_id(target_id.clone())
+                    .build(),
+            )
+            .await;
+        if let Err(error) = detached {
+            log::warn!("failed to detach from {:?}: {}", target_id, error);
+        }
+        bail!("no page for target {:?}", target_id);
+    };
+
+    let result = async {
+        setup.apply(&page).await?;
+        forward_page_events(&page, tabs, sender.clone()).await
+    }
+    .await;
+    // The page waits for this, set up or not, before loading anything.
+    page.execute(runtime::RunIfWaitingForDebuggerParams::default())
+        .await?;
+    result?;
+
+    // The main frame of a page has the same ID as its target.
+    let frame_id = page
+        .mainframe()
+        .await?
+        .unwrap_or_else(|| FrameId::new(target_id.inner().clone()));
+    sender.send(InnerEvent::TabOpened(page, frame_id))?;
+    Ok(())
+}
+
+/// Forwards the events of a page to the state machine, as long as it's the
+/// active tab.
+async fn forward_page_events(
+    page: &Page,
+    tabs: &Tabs,
+    sender: Sender<InnerEvent>,
+) -> Result<()> {
+    let mut events = page_events(page).await?;
+    let target_id = page.target_id().clone();
+    let tabs = tabs.clone();
+    spawn(async move {
+        while let Some(event) = events.next().await {
+            if tabs.is_active(&target_id) && sender.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
 /// Auto-accept JavaScript dialogs (alert, confirm, prompt, beforeunload)
 /// so they never block the test run.
 async fn auto_accept_dialogs(page: Arc<Page>) -> Result<()> {
@@ -463,47 +893,31 @@ async fn auto_accept_dialogs(page: Arc<Page>) -> Result<()> {
     Ok(())
 }
 
-async fn inner_events(
-    context: &BrowserContext,
-) -> Result<Pin<Box<dyn stream::Stream<Item = InnerEvent> + Send>>> {
-    type InnerEventStream =
-        Pin<Box<dyn stream::Stream<Item = InnerEvent> + Send>>;
-
+async fn page_events(page: &Page) -> Result<InnerEventStream> {
     let events_loaded = Box::pin(
-        context
-            .page
-            .event_listener::<page::EventLoadEventFired>()
+        page.event_listener::<page::EventLoadEventFired>()
             .await?
             .map(|_| InnerEvent::Loaded),
     ) as InnerEventStream;
 
-    let events_paused = Box::pin(
-        context
-            .page
-            .event_listener::<debugger::EventPaused>()
-            .await?
-            .map(|event| InnerEvent::Paused {
+    let events_paused =
+        Box::pin(page.event_listener::<debugger::EventPaused>().await?.map(
+            |event| InnerEvent::Paused {
                 reason: event.reason.clone(),
                 exception: event.data.clone(),
-                call_frame_id: event
-                    .call_frames
-                    .first()
-                    .map(|f| f.call_frame_id.clone()),
-            }),
-    ) as InnerEventStream;
+                call_frame_id:
+                    event.call_frames.first().map(|f| f.call_frame_id.clone()),
+            },
+        )) as InnerEventStream;
 
     let events_resumed = Box::pin(
-        context
-            .page
-            .event_listener::<debugger::EventResumed>()
+        page.event_listener::<debugger::EventResumed>()
             .await?
             .map(|_| InnerEvent::Resumed),
     ) as InnerEventStream;
 
     let events_exception_thrown = Box::pin(
-        context
-            .page
-            .event_listener::<runtime::EventExceptionThrown>()
+        page.event_listener::<runtime::EventExceptionThrown>()
             .await?
             .map(|e| {
                 InnerEvent::ExceptionThrown(Exception {
@@ -547,9 +961,7 @@ async fn inner_events(
     ) as InnerEventStream;
 
     let events_frame_requested_navigation = Box::pin(
-        context
-            .page
-            .event_listener::<page::EventFrameRequestedNavigation>()
+        page.event_listener::<page::EventFrameRequestedNavigation>()
             .await?
             .map(|nav| InnerEvent::FrameRequestedNavigation {
                 frame_id: nav.frame_id.clone(),
@@ -559,9 +971,7 @@ async fn inner_events(
     ) as InnerEventStream;
 
     let events_frame_navigated = Box::pin(
-        context
-            .page
-            .event_listener::<page::EventFrameNavigated>()
+        page.event_listener::<page::EventFrameNavigated>()
             .await?
             .map(|nav| {
                 InnerEvent::FrameNavigated(
@@ -572,9 +982,7 @@ async fn inner_events(
     ) as InnerEventStream;
 
     let events_download_will_begin = Box::pin(
-        context
-            .page
-            .event_listener::<browser::EventDownloadWillBegin>()
+        page.event_listener::<browser::EventDownloadWillBegin>()
             .await?
             .map(|event| InnerEvent::DownloadWillBegin {
                 frame_id: event.frame_id.clone(),
@@ -582,18 +990,8 @@ async fn inner_events(
             }),
     ) as InnerEventStream;
 
-    let events_target_destroyed = Box::pin(
-        context
-            .page
-            .event_listener::<target::EventTargetDestroyed>()
-            .await?
-            .map(|event| InnerEvent::TargetDestroyed(event.target_id.clone())),
-    ) as InnerEventStream;
-
     // let events_node_inserted = Box::pin(
-    //     context
-    //         .page
-    //         .event_listener::<dom::EventChildNodeInserted>()
+    //     page.event_listener::<dom::EventChildNodeInserted>()
     //         .await?
     //         .map(|event| {
     //             InnerEvent::NodeTreeModified(
@@ -606,9 +1004,7 @@ async fn inner_events(
     // ) as InnerEventStream;
 
     // let events_node_count_updated = Box::pin(
-    //     context
-    //         .page
-    //         .event_listener::<dom::EventChildNodeCountUpdated>()
+    //     page.event_listener::<dom::EventChildNodeCountUpdated>()
     //         .await?
     //         .map(|event| {
     //             InnerEvent::NodeTreeModified(
@@ -621,9 +1017,7 @@ async fn inner_events(
     // ) as InnerEventStream;
 
     // let events_node_removed = Box::pin(
-    //     context
-    //         .page
-    //         .event_listener::<dom::EventChildNodeRemoved>()
+    //     page.event_listener::<dom::EventChildNodeRemoved>()
     //         .await?
     //         .map(|event| {
     //             InnerEvent::NodeTreeModified(
@@ -636,9 +1030,7 @@ async fn inner_events(
     // ) as InnerEventStream;
 
     // let events_attribute_modified = Box::pin(
-    //     context
-    //         .page
-    //         .event_listener::<dom::EventAttributeModified>()
+    //     page.event_listener::<dom::EventAttributeModified>()
     //         .await?
     //         .map(|event| {
     //             InnerEvent::NodeTreeModified(
@@ -652,9 +1044,7 @@ async fn inner_events(
     // ) as InnerEventStream;
 
     let events_console = Box::pin(
-        context
-            .page
-            .event_listener::<runtime::EventConsoleApiCalled>()
+        page.event_listener::<runtime::EventConsoleApiCalled>()
             .await?
             .filter_map(async |call| {
                 let level = match call.r#type {
@@ -678,10 +1068,36 @@ async fn inner_events(
             }),
     ) as InnerEventStream;
 
-    let events_action_accepted =
-        Box::pin(receiver_to_stream(context.actions_sender.subscribe()).map(
-            |(action, timeout)| InnerEvent::ActionAccepted(action, timeout),
-        ));
+    let events_network = Box::pin(
+        stream::select_all(vec![
+            Box::pin(
+                page.event_listener::<network::EventRequestWillBeSent>()
+                    .await?
+                    .map(NetworkEvent::RequestWillBeSent),
+            ) as NetworkEventStream,
+            Box::pin(
+                page.event_listener::<network::EventResponseReceived>()
+                    .await?
+                    .map(NetworkEvent::ResponseReceived),
+            ),
+            Box::pin(
+                page.event_listener::<network::EventLoadingFinished>()
+                    .await?
+                    .map(NetworkEvent::LoadingFinished),
+            ),
+            Box::pin(
+                page.event_listener::<network::EventLoadingFailed>()
+                    .await?
+                    .map(NetworkEvent::LoadingFailed),
+            ),
+        ])
+        .scan(HashMap::new(), |pending, event| {
+            future::ready(Some(track_network_request(pending, event)))
+        })
+        .filter_map(|request| {
+            future::ready(request.map(InnerEvent::NetworkRequest))
+        }),
+    ) as InnerEventStream;
 
     Ok(Box::pin(stream::select_all(vec![
         events_loaded,
@@ -691,13 +1107,12 @@ async fn inner_events(
         events_frame_requested_navigation,
         events_frame_navigated,
         events_download_will_begin,
-        events_target_destroyed,
         // events_node_inserted,
         // events_node_count_updated,
         // events_node_removed,
         // events_attribute_modified,
         events_console,
-        events_action_accepted,
+        events_network,
     ])))
 }
 
@@ -761,7 +1176,7 @@ async fn process_event(
                 state
             } else if matches!(
                 state.kind,
-                Navigating { .. } | Loading | Paused | Pausing
+                Navigating { .. } | Loading | Paused | Pausing | Resetting
             ) {
                 log::debug!(
                     "skipping state capture during {:?} (reason: {:?})",
@@ -789,7 +1204,8 @@ async fn process_event(
                 "paused without call frame, resuming and retrying capture"
             );
             context
-                .page
+                .tabs
+                .active()
                 .execute(debugger::ResumeParams::builder().build())
                 .await?;
             capture_browser_state(
@@ -819,24 +1235,22 @@ async fn process_event(
                 );
             }
 
-            let InnerStateShared {
-                console_entries,
-                exceptions,
-                generation,
-                screenshot,
-            } = state.shared;
-
-            let screenshot = screenshot
-                .ok_or(anyhow!("no screenshot available for state capture"))?;
-
-            let browser_state = BrowserState::current(
-                context.page.clone(),
+            let generation = state.shared.generation;
+            let shared = InnerStateShared {
+                tabs: context.tabs.describe().await?,
+                connectivity: context.network_conditions.connectivity(),
+                touch: context.emulation.mobile,
+                ..state.shared
+            };
+            let mut browser_state = BrowserState::current(
+                context.tabs.active(),
                 &call_frame_id,
-                console_entries,
-                exceptions,
-                screenshot,
+                shared,
             )
             .await?;
+            if let Some(clock) = &context.clock {
+                browser_state.timestamp = clock.timestamp();
+            }
 
             context
                 .sender
@@ -858,9 +1272,7 @@ async fn process_event(
                 kind: Paused,
                 shared: InnerStateShared {
                     generation,
-                    console_entries: vec![],
-                    exceptions: vec![],
-                    screenshot: None,
+                    ..InnerStateShared::default()
                 },
             }
         }
@@ -872,7 +1284,8 @@ async fn process_event(
             InnerEvent::ActionAccepted(browser_action, timeout),
         ) => {
             context
-                .page
+                .tabs
+                .active()
                 .execute(debugger::ResumeParams::builder().build())
                 .await?;
             InnerState {
@@ -880,6 +1293,72 @@ async fn process_event(
                 shared,
             }
         }
+        (
+            InnerState {
+                kind: Paused,
+                shared,
+            },
+            InnerEvent::ResetRequested,
+        ) => {
+            log::info!("clearing cookies and storage for {}", context.origin);
+            context
+                .tabs
+                .active()
+                .execute(network::ClearBrowserCookiesParams {})
+                .await?;
+            // Not all origins have storage (e.g. file URLs), so this is
+            // allowed to fail.
+            if let Err(error) = context
+                .tabs
+                .active()
+                .execute(storage::ClearDataForOriginParams::new(
+                    context.origin.origin().ascii_serialization(),
+                    "all",
+                ))
+                .await
+            {
+                log::warn!("failed to clear storage: {}", error);
+            }
+            context.faults.clear(&context.tabs.active()).await?;
+            context
+                .network_conditions
+                .set_online(&context.tabs.pages(), true)
+                .await?;
+            context
+                .tabs
+                .active()
+                .execute(debugger::ResumeParams::builder().build())
+                .await?;
+            InnerState {
+                kind: Resetting,
+                shared,
+            }
+        }
+        (
+            InnerState {
+                kind: Resetting,
+                mut shared,
+            },
+            InnerEvent::Resumed,
+        ) => {
+            context.tabs.close_others().await?;
+            let page = context.tabs.active();
+            // Undo any resizing.
+            let emulation = &context.emulation;
+            emulation
+                .set_viewport(&page, emulation.width, emulation.height)
+                .await?;
+            spawn(go_to_origin(
+                page,
+                context.origin.clone(),
+                context.storage_seed.clone(),
+            ));
+            shared.console_entries.clear();
+            InnerState {
+                kind: Running,
+                shared,
+            }
+        }
         (
             state @ InnerState {
                 kind: Loading | Navigating { .. } | Pausing,
@@ -928,15 +1407,38 @@ async fn process_event(
             },
             InnerEvent::Resumed,
         ) => {
-            let page = context.page.clone();
+            let page = context.tabs.active();
+            let faults = context.faults.clone();
+            let tabs = context.tabs.clone();
+            let emulation = context.emulation.clone();
+            let network_conditions = context.network_conditions.clone();
+            let clock = context.clock.clone();
+            let applied_actions = context.applied_actions.clone();
+            let events_sender = context.sender.clone();
             let sender = context.inner_events_sender.clone();
+            // Set by whichever finishes first, applying or timing out.
+            let finished = Arc::new(AtomicBool::new(false));
+            let action_finished = finished.clone();
+            let timed_out_action = browser_action.clone();
             // We can't block on running the action, in case it synchronously
             // throws an uncaught exception blocking the evaluation indefinitely.
             // This gives us a chance to receive the "Debugger.paused" event and
             // resume (extracting the uncaught exception information).
             let action_handle = spawn(async move {
                 log::debug!("applying: {:?}", browser_action);
-                match browser_action.apply(&page).await {
+                let result = browser_action
+                    .apply(
+                        &page,
+                        &faults,
+                        &tabs,
+                        &emulation,
+                        &network_conditions,
+                    )
+                    .await;
+                if action_finished.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+                match result {
                     Ok(_) => {
                         log::debug!("applied: {:?}", browser_action);
                     }
@@ -945,20 +1447,77 @@ async fn process_event(
                             "failed to apply action {:?}: {:?}",
                             browser_action,
                             err
-                        )
+                        );
+                        let _ = events_sender.send(BrowserEvent::ActionFailed(
+                            browser_action,
+                            Arc::new(err),
+                        ));
                     }
                 }
+                applied_actions.fetch_add(1, Ordering::SeqCst);
                 if let Err(error) =
                     sender.send(InnerEvent::ActionApplied(shared.generation))
                 {
                     log::error!("failed to send ActionApplied: {}", error);
                 }
+                // Under virtual time, the page only gets the action's timeout
+                // worth of virtual time to react, after which a new state is
+                // captured, regardless of how long that takes on the wall
+                // clock.
+                if let Some(clock) = clock {
+                    // The action might have switched tabs.
+                    let page = tabs.active();
+                    if let Err(error) = clock.advance(&page, timeout).await {
+                        log::error!(
+                            "failed to advance virtual time: {}",
+                            error
+                        );
+                    }
+                    if let Err(error) = sender.send(InnerEvent::StateRequested(
+                        StateRequestReason::Timeout,
+                        shared.generation,
+                    )) {
+                        log::error!(
+                            "failed to send StateRequested after advancing \
+                             virtual time: {}",
+                            error
+                        );
+                    }
+                }
             });
 
+            // Under virtual time this is only a fallback, in case advancing
+            // the clock gets stuck. Advancing it takes no longer on the wall
+            // clock for a day than for a second, so the fallback for
+            // `AdvanceClock` doesn't depend on its duration.
+            let timeout = match (&context.clock, &timed_out_action) {
+                (Some(_), BrowserAction::AdvanceClock { .. }) => {
+                    VIRTUAL_TIME_GRACE
+                }
+                (Some(_), _) => timeout + VIRTUAL_TIME_GRACE,
+                (None, _) => timeout,
+            };
             let sender = context.inner_events_sender.clone();
+            let events_sender = context.sender.clone();
+            let applied_actions = context.applied_actions.clone();
             spawn(async move {
                 sleep(timeout).await;
                 action_handle.abort();
+                if !finished.swap(true, Ordering::SeqCst) {
+                    log::error!(
+                        "action {:?} didn't finish within {}ms",
+                        timed_out_action,
+                        timeout.as_millis()
+                    );
+                    let _ = events_sender.send(BrowserEvent::ActionFailed(
+                        timed_out_action,
+                        Arc::new(anyhow!(
+                            "didn't finish within {}ms",
+                            timeout.as_millis()
+                        )),
+                    ));
+                    applied_actions.fetch_add(1, Ordering::SeqCst);
+                }
                 log::debug!(
                     "timeout after {}ms, aborted action, requesting new state",
                     timeout.as_millis()
@@ -1014,7 +1573,7 @@ async fn process_event(
                 url,
             },
         ) => {
-            if frame_id == context.frame_id {
+            if frame_id == context.tabs.active_frame_id() {
                 log::debug!(
                     "navigating to {} due to {:?} (current state is {:?}, {})",
                     url,
@@ -1043,7 +1602,9 @@ async fn process_event(
                 frame_id,
             },
         ) => {
-            if frame_id == context.frame_id && url_navigating == url_download {
+            if frame_id == context.tabs.active_frame_id()
+                && url_navigating == url_download
+            {
                 let _ = context.inner_events_sender.send(
                     InnerEvent::StateRequested(
                         StateRequestReason::FileDownload,
@@ -1081,6 +1642,10 @@ async fn process_event(
             state.shared.console_entries.push(entry);
             state
         }
+        (mut state, InnerEvent::NetworkRequest(request)) => {
+            state.shared.network_requests.push(request);
+            state
+        }
         (mut state, InnerEvent::ExceptionThrown(exception)) => {
             state.shared.exceptions.push(exception);
             if matches!(state.kind, Running) {
@@ -1092,7 +1657,8 @@ async fn process_event(
         (state, InnerEvent::FrameNavigated(frame_id, navigation_type)) => {
             // Track all nodes.
             context
-                .page
+                .tabs
+                .active()
                 .execute(
                     dom::GetDocumentParams::builder()
                         .depth(-1)
@@ -1100,7 +1666,7 @@ async fn process_event(
                         .build(),
                 )
                 .await?;
-            if frame_id == context.frame_id {
+            if frame_id == context.tabs.active_frame_id() {
                 let shared = state.shared;
                 let kind = match navigation_type {
                     NavigationType::Navigation => Loading,
@@ -1122,9 +1688,38 @@ async fn process_event(
             }
         }
         (state, InnerEvent::TargetDestroyed(target_id)) => {
-            if target_id == *context.page.target_id() {
-                bail!("page target {:?} was destroyed", target_id);
+            match context.tabs.remove(&target_id) {
+                Some(true) => {
+                    log::info!("active tab was closed, switching tabs");
+                    context
+                        .tabs
+                        .active()
+                        .execute(page::BringToFrontParams::default())
+                        .await?;
+                    switch_tab(state, context)?
+                }
+                Some(false) => state,
+                None if context.tabs.is_active(&target_id) => {
+                    bail!("page target {:?} was destroyed", target_id);
+                }
+                None => state,
+            }
+        }
+        (state, InnerEvent::TabOpened(page, frame_id)) => {
+            context.tabs.open(page, frame_id);
+            // Switching while the active page is paused, or about to be,
+            // would leave it paused.
+            if matches!(
+                state.kind,
+                Running | Acting | Loading | Navigating { .. }
+            ) {
+                context.tabs.switch_to_last().await?;
+                switch_tab(state, context)?
             } else {
+                log::debug!(
+                    "not switching to new tab during {:?}",
+                    &state.kind
+                );
                 state
             }
         }
@@ -1134,13 +1729,30 @@ async fn process_event(
     })
 }
 
+/// Continues in the newly active tab, capturing its state.
+fn switch_tab(
+    state: InnerState,
+    context: &BrowserContext,
+) -> Result<InnerState> {
+    context
+        .inner_events_sender
+        .send(InnerEvent::StateRequested(
+            StateRequestReason::TabChanged,
+            state.shared.generation,
+        ))?;
+    Ok(InnerState {
+        kind: InnerStateKind::Running,
+        shared: state.shared,
+    })
+}
+
 async fn capture_browser_state(
     mut state: InnerState,
     context: &BrowserContext,
 ) -> Result<InnerState> {
     log::debug!("pausing, going into next generation...");
 
-    let page = context.page.clone();
+    let page = context.tabs.active();
     let main_execution_context_id = match page.execution_context().await? {
         Some(ctx) => ctx,
         None => {
@@ -1155,7 +1767,7 @@ async fn capture_browser_state(
     let format = ScreenshotFormat::Webp;
     let screenshot_result = tokio::time::timeout(
         Duration::from_secs(2),
-        context.page.screenshot(
+        context.tabs.active().screenshot(
             ScreenshotParams::builder()
                 .omit_background(true)
                 .format(format)
@@ -1177,11 +1789,52 @@ async fn capture_browser_state(
     };
     state.shared.screenshot = Some(screenshot);
 
+    state.shared.applied_actions =
+        context.applied_actions.load(Ordering::SeqCst);
+
+    log::debug!("capturing storage before pause");
+    state.shared.storage = match tokio::time::timeout(
+        Duration::from_secs(2),
+        capture_storage(&page),
+    )
+    .await
+    {
+        Ok(Ok(storage)) => storage,
+        Ok(Err(error)) => {
+            log::warn!("storage capture failed: {}", error);
+            Storage::default()
+        }
+        Err(_) => {
+            log::warn!("storage capture timed out");
+            Storage::default()
+        }
+    };
+
+    if context.accessibility_tree {
+        log::debug!("capturing accessibility tree before pause");
+        state.shared.accessibility_tree = match tokio::time::timeout(
+            Duration::from_secs(2),
+            capture_accessibility_tree(&page),
+        )
+        .await
+        {
+            Ok(Ok(nodes)) => Some(nodes),
+            Ok(Err(error)) => {
+                log::warn!("accessibility tree capture failed: {}", error);
+                None
+            }
+            Err(_) => {
+                log::warn!("accessibility tree capture timed out");
+                None
+            }
+        };
+    }
+
     // context
     //     .page
     //     .execute(debugger::PauseParams::default())
     //     .await?;
-    let page = context.page.clone();
+    let page = context.tabs.active();
     spawn(async move {
         let _ = page
             .execute(
@@ -1202,6 +1855,97 @@ async fn capture_browser_state(
     })
 }
 
+enum NetworkEvent {
+    RequestWillBeSent(Arc<network::EventRequestWillBeSent>),
+    ResponseReceived(Arc<network::EventResponseReceived>),
+    LoadingFinished(Arc<network::EventLoadingFinished>),
+    LoadingFailed(Arc<network::EventLoadingFailed>),
+}
+
+type NetworkEventStream =
+    Pin<Box<dyn stream::Stream<Item = NetworkEvent> + Send>>;
+
+/// A request that has been sent but hasn't finished or failed yet.
+struct PendingRequest {
+    request: NetworkRequest,
+    /// Monotonic time in seconds, as given by CDP, for the duration.
+    sent: f64,
+}
+
+impl PendingRequest {
+    fn finish(
+        mut self,
+        timestamp: &network::MonotonicTime,
+        failure: Option<String>,
+    ) -> NetworkRequest {
+        self.request.duration_millis =
+            ((*timestamp.inner() - self.sent) * 1000.0).max(0.0);
+        self.request.failure = failure;
+        self.request
+    }
+}
+
+/// Follows requests through their CDP events, and returns them once they
+/// have finished or failed. Redirects finish the request for the previous
+/// URL.
+fn track_network_request(
+    pending: &mut HashMap<network::RequestId, PendingRequest>,
+    event: NetworkEvent,
+) -> Option<NetworkRequest> {
+    match event {
+        NetworkEvent::RequestWillBeSent(event) => {
+            let redirected =
+                event.redirect_response.as_ref().and_then(|response| {
+                    let mut previous = pending.remove(&event.request_id)?;
+                    previous.request.status =
+                        u16::try_from(response.status).ok();
+                    Some(previous.finish(&event.timestamp, None))
+                });
+            pending.insert(
+                event.request_id.clone(),
+                PendingRequest {
+                    request: NetworkRequest {
+                        timestamp: UNIX_EPOCH
+                            + Duration::from_secs_f64(
+                                event.wall_time.inner().max(0.0),
+                            ),
+                        url: event.request.url.clone(),
+                        method: event.request.method.clone(),
+                        resource_type: event.r#type.as_ref().map(
+                            |resource_type| resource_type.as_ref().to_string(),
+                        ),
+                        status: None,
+                        duration_millis: 0.0,
+                        failure: None,
+                    },
+                    sent: *event.timestamp.inner(),
+                },
+            );
+            redirected
+        }
+        NetworkEvent::ResponseReceived(event) => {
+            if let Some(pending) = pending.get_mut(&event.request_id) {
+                pending.request.status =
+                    u16::try_from(event.response.status).ok();
+            }
+            None
+        }
+        NetworkEvent::LoadingFinished(event) => pending
+            .remove(&event.request_id)
+            .map(|pending| pending.finish(&event.timestamp, None)),
+        NetworkEvent::LoadingFailed(event) => {
+            let failure = if event.canceled == Some(true) {
+                "canceled".to_string()
+            } else {
+                event.error_text.clone()
+            };
+            pending
+                .remove(&event.request_id)
+                .map(|pending| pending.finish(&event.timestamp, Some(failure)))
+        }
+    }
+}
+
 fn receiver_to_stream<T: Clone + Send + 'static>(
     receiver: Receiver<T>,
 ) -> Pin<Box<dyn stream::Stream<Item = T> + Send>> {
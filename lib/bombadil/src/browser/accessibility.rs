@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chromiumoxide::Page;
+use chromiumoxide::cdp::browser_protocol::accessibility::{
+    self, AxNode, AxValue,
+};
+use serde_json as json;
+
+use crate::browser::state::AccessibilityNode;
+
+/// Fetches the accessibility tree of the page's main frame, as computed by
+/// the browser for assistive technology.
+pub async fn capture_accessibility_tree(
+    page: &Page,
+) -> Result<Vec<AccessibilityNode>> {
+    let nodes = page
+        .execute(accessibility::GetFullAxTreeParams::default())
+        .await?
+        .result
+        .nodes;
+    Ok(nodes.iter().map(accessibility_node).collect())
+}
+
+fn accessibility_node(node: &AxNode) -> AccessibilityNode {
+    AccessibilityNode {
+        id: node.node_id.inner().clone(),
+        parent_id: node.parent_id.as_ref().map(|id| id.inner().clone()),
+        child_ids: node
+            .child_ids
+            .iter()
+            .flatten()
+            .map(|id| id.inner().clone())
+            .collect(),
+        role: node.role.as_ref().and_then(text),
+        name: node.name.as_ref().and_then(text),
+        description: node.description.as_ref().and_then(text),
+        value: node.value.as_ref().and_then(text),
+        ignored: node.ignored,
+        ignored_reasons: node
+            .ignored_reasons
+            .iter()
+            .flatten()
+            .map(|reason| reason.name.as_ref().to_string())
+            .collect(),
+        properties: node
+            .properties
+            .iter()
+            .flatten()
+            .filter_map(|property| {
+                Some((
+                    property.name.as_ref().to_string(),
+                    property.value.value.clone()?,
+                ))
+            })
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+/// The value as text, where roles and names are strings, and values of
+/// range inputs are numbers.
+fn text(value: &AxValue) -> Option<String> {
+    match value.value.as_ref()? {
+        json::Value::String(string) => Some(string.clone()),
+        json::Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessibility_node_flattens_values() {
+        let node: AxNode = json::from_value(json::json!({
+            "nodeId": "7",
+            "ignored": false,
+            "role": { "type": "role", "value": "slider" },
+            "name": { "type": "computedString", "value": "Volume" },
+            "value": { "type": "number", "value": 40 },
+            "properties": [
+                { "name": "focusable", "value": { "type": "booleanOrUndefined", "value": true } },
+                { "name": "valuemax", "value": { "type": "number", "value": 100 } }
+            ],
+            "parentId": "3",
+            "childIds": ["8"]
+        }))
+        .unwrap();
+        let node = accessibility_node(&node);
+        assert_eq!(node.role.as_deref(), Some("slider"));
+        assert_eq!(node.name.as_deref(), Some("Volume"));
+        assert_eq!(node.value.as_deref(), Some("40"));
+        assert_eq!(node.parent_id.as_deref(), Some("3"));
+        assert_eq!(node.child_ids, vec!["8".to_string()]);
+        assert_eq!(node.properties["focusable"], json::json!(true));
+        assert_eq!(node.properties["valuemax"], json::json!(100));
+    }
+}
@@ -3,11 +3,18 @@ use std::time::Duration;
 use anyhow::{Result, anyhow, bail};
 use chromiumoxide::Page;
 use chromiumoxide::cdp::browser_protocol::{dom, input, page};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use serde_json as json;
 use tokio::time::sleep;
 
+use crate::browser::Emulation;
+use crate::browser::instrumentation::NetworkFaults;
+use crate::browser::network_conditions::NetworkConditions;
+use crate::browser::tabs::Tabs;
 use crate::geometry::Point;
-use bombadil_browser_keys::key_name;
+use bombadil_browser_keys::{Modifiers, key_name};
+pub use bombadil_schema::KeyModifiers;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BrowserAction {
@@ -30,6 +37,7 @@ pub enum BrowserAction {
     },
     PressKey {
         code: u8,
+        modifiers: KeyModifiers,
     },
     ScrollUp {
         origin: Point,
@@ -45,10 +53,370 @@ pub enum BrowserAction {
         selector: String,
         files: Vec<String>,
     },
+    SelectOptions {
+        selector: String,
+        values: Vec<String>,
+    },
+    SetInputValue {
+        selector: String,
+        value: String,
+    },
+    /// Makes the first following request matching the URL pattern (with `*`
+    /// and `?` wildcards) fail, get an error status, or be delayed.
+    InjectFault {
+        url_pattern: String,
+        fault: NetworkFault,
+    },
+    GoOffline,
+    GoOnline,
+    /// Like `Wait`, but for a given duration. Under virtual time, that much
+    /// virtual time passes after the action instead.
+    AdvanceClock {
+        millis: u64,
+    },
+    /// Changes the size of the viewport, keeping the rest of the emulated
+    /// device.
+    Resize {
+        width: u16,
+        height: u16,
+    },
+    /// Makes the tab with the given index, in the order tabs were opened,
+    /// the active one.
+    SwitchTab {
+        index: u32,
+    },
+    /// Closes the tab with the given index, switching to the previous one if
+    /// it was active.
+    CloseTab {
+        index: u32,
+    },
+    Tap {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    LongPress {
+        name: String,
+        content: Option<String>,
+        point: Point,
+        duration_millis: u64,
+    },
+    Swipe {
+        start: Point,
+        end: Point,
+    },
+    Pinch {
+        origin: Point,
+        scale: f64,
+    },
+    Drag {
+        from: Point,
+        to: Point,
+        steps: u32,
+    },
+    Hover {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    ContextClick {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    /// Runs the specification's setup script in the page, waiting for the
+    /// promise it returns.
+    RunSetupScript,
+}
+
+/// Number of intermediate touch moves in swipes and pinches.
+const TOUCH_MOVE_STEPS: u32 = 10;
+
+/// Delay between mouse and touch moves, roughly one frame.
+const POINTER_MOVE_DELAY: Duration = Duration::from_millis(16);
+
+/// How long to wait for an HTML5 drag to start after moving the mouse.
+const DRAG_INTERCEPT_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Distance between the two fingers at the start of a pinch.
+const PINCH_SPAN: f64 = 100.0;
+
+/// Selects exactly the options with the given values, and notifies the page
+/// like a user selection would.
+const SELECT_OPTIONS_FUNCTION: &str = r#"(selector, values) => {
+    const element = document.querySelector(selector);
+    if (!(element instanceof HTMLSelectElement)) {
+        throw new Error(`no select element matches ${selector}`);
+    }
+    for (const option of element.options) {
+        option.selected = values.includes(option.value);
+    }
+    element.dispatchEvent(new Event("input", { bubbles: true }));
+    element.dispatchEvent(new Event("change", { bubbles: true }));
+}"#;
+
+/// Runs the setup script registered by the specification bundle, which is
+/// evaluated in every document.
+const RUN_SETUP_SCRIPT_EXPRESSION: &str = "__bombadilRequire('@antithesishq/bombadil').runtime.runSetup({ document, window })";
+
+/// Sets an input's value through the native setter, so that frameworks
+/// tracking the value see the change, and notifies the page like a user
+/// edit would.
+const SET_INPUT_VALUE_FUNCTION: &str = r#"(selector, value) => {
+    const element = document.querySelector(selector);
+    if (!(element instanceof HTMLInputElement)) {
+        throw new Error(`no input element matches ${selector}`);
+    }
+    Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, "value")
+        .set.call(element, value);
+    element.dispatchEvent(new Event("input", { bubbles: true }));
+    element.dispatchEvent(new Event("change", { bubbles: true }));
+}"#;
+
+async fn call_function(
+    page: &Page,
+    function: &str,
+    arguments: &[json::Value],
+) -> Result<()> {
+    let arguments = arguments
+        .iter()
+        .map(json::to_string)
+        .collect::<json::Result<Vec<_>>>()?;
+    page.evaluate(format!("({})({})", function, arguments.join(", ")))
+        .await?;
+    Ok(())
+}
+
+fn key_event(
+    event_type: input::DispatchKeyEventType,
+    code: u8,
+    modifiers: i64,
+    text: Option<&str>,
+) -> Result<input::DispatchKeyEventParams> {
+    let Some(name) = key_name(code) else {
+        bail!("unknown key with code: {:?}", code)
+    };
+    let mut builder = input::DispatchKeyEventParams::builder()
+        .r#type(event_type)
+        .native_virtual_key_code(code as i64)
+        .windows_virtual_key_code(code as i64)
+        .code(name)
+        .key(name)
+        .modifiers(modifiers);
+    if let Some(text) = text {
+        builder = builder.unmodified_text(text).text(text);
+    }
+    builder.build().map_err(|err| anyhow!(err))
+}
+
+async fn dispatch_touch(
+    page: &Page,
+    event_type: input::DispatchTouchEventType,
+    points: &[Point],
+) -> Result<()> {
+    let touch_points = points
+        .iter()
+        .enumerate()
+        .map(|(id, point)| {
+            input::TouchPoint::builder()
+                .x(point.x)
+                .y(point.y)
+                .id(id as f64)
+                .build()
+                .map_err(|err| anyhow!(err))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    page.execute(
+        input::DispatchTouchEventParams::builder()
+            .r#type(event_type)
+            .touch_points(touch_points)
+            .build()
+            .map_err(|err| anyhow!(err))?,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn dispatch_mouse(
+    page: &Page,
+    event_type: input::DispatchMouseEventType,
+    point: Point,
+) -> Result<()> {
+    page.execute(
+        input::DispatchMouseEventParams::builder()
+            .r#type(event_type)
+            .x(point.x)
+            .y(point.y)
+            .button(input::MouseButton::Left)
+            .buttons(1)
+            .click_count(1)
+            .build()
+            .map_err(|err| anyhow!(err))?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Presses the left mouse button, moves the mouse and releases it. Chrome
+/// doesn't run HTML5 drag and drop for synthesized mouse events, so drags are
+/// intercepted, and if one starts, its drag events are dispatched on the way
+/// to the release point.
+async fn drag(page: &Page, from: Point, to: Point, steps: u32) -> Result<()> {
+    let mut intercepted =
+        page.event_listener::<input::EventDragIntercepted>().await?;
+    page.execute(input::SetInterceptDragsParams::new(true))
+        .await?;
+
+    let result = async {
+        dispatch_mouse(page, input::DispatchMouseEventType::MouseMoved, from)
+            .await?;
+        dispatch_mouse(page, input::DispatchMouseEventType::MousePressed, from)
+            .await?;
+        let mut drag_data = None;
+        for step in 1..=steps {
+            sleep(POINTER_MOVE_DELAY).await;
+            let t = step as f64 / steps as f64;
+            let point = Point {
+                x: from.x + (to.x - from.x) * t,
+                y: from.y + (to.y - from.y) * t,
+            };
+            match &drag_data {
+                None => {
+                    dispatch_mouse(
+                        page,
+                        input::DispatchMouseEventType::MouseMoved,
+                        point,
+                    )
+                    .await?;
+                    if let Ok(Some(event)) = tokio::time::timeout(
+                        DRAG_INTERCEPT_TIMEOUT,
+                        intercepted.next(),
+                    )
+                    .await
+                    {
+                        let data = event.data.clone();
+                        dispatch_drag(
+                            page,
+                            input::DispatchDragEventType::DragEnter,
+                            point,
+                            &data,
+                        )
+                        .await?;
+                        drag_data = Some(data);
+                    }
+                }
+                Some(data) => {
+                    dispatch_drag(
+                        page,
+                        input::DispatchDragEventType::DragOver,
+                        point,
+                        data,
+                    )
+                    .await?;
+                }
+            }
+        }
+        if let Some(data) = &drag_data {
+            dispatch_drag(page, input::DispatchDragEventType::Drop, to, data)
+                .await?;
+        }
+        dispatch_mouse(page, input::DispatchMouseEventType::MouseReleased, to)
+            .await
+    }
+    .await;
+
+    page.execute(input::SetInterceptDragsParams::new(false))
+        .await?;
+    result
+}
+
+async fn dispatch_drag(
+    page: &Page,
+    event_type: input::DispatchDragEventType,
+    point: Point,
+    data: &input::DragData,
+) -> Result<()> {
+    page.execute(
+        input::DispatchDragEventParams::builder()
+            .r#type(event_type)
+            .x(point.x)
+            .y(point.y)
+            .data(data.clone())
+            .build()
+            .map_err(|err| anyhow!(err))?,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Touches the page with one point per finger, moves the fingers from their
+/// start to their end points, and lifts them.
+async fn touch_gesture(page: &Page, fingers: &[(Point, Point)]) -> Result<()> {
+    let at = |t: f64| {
+        fingers
+            .iter()
+            .map(|(start, end)| Point {
+                x: start.x + (end.x - start.x) * t,
+                y: start.y + (end.y - start.y) * t,
+            })
+            .collect::<Vec<_>>()
+    };
+    dispatch_touch(page, input::DispatchTouchEventType::TouchStart, &at(0.0))
+        .await?;
+    for step in 1..=TOUCH_MOVE_STEPS {
+        sleep(POINTER_MOVE_DELAY).await;
+        dispatch_touch(
+            page,
+            input::DispatchTouchEventType::TouchMove,
+            &at(step as f64 / TOUCH_MOVE_STEPS as f64),
+        )
+        .await?;
+    }
+    dispatch_touch(page, input::DispatchTouchEventType::TouchEnd, &[]).await
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NetworkFault {
+    Fail,
+    Status { code: u16 },
+    Delay { millis: u64 },
+}
+
+impl NetworkFault {
+    pub fn from_api(fault: &bombadil_schema::NetworkFault) -> Self {
+        match fault {
+            bombadil_schema::NetworkFault::Fail => NetworkFault::Fail,
+            bombadil_schema::NetworkFault::Status { code } => {
+                NetworkFault::Status { code: *code }
+            }
+            bombadil_schema::NetworkFault::Delay { millis } => {
+                NetworkFault::Delay { millis: *millis }
+            }
+        }
+    }
+
+    pub fn to_api(&self) -> bombadil_schema::NetworkFault {
+        match self {
+            NetworkFault::Fail => bombadil_schema::NetworkFault::Fail,
+            NetworkFault::Status { code } => {
+                bombadil_schema::NetworkFault::Status { code: *code }
+            }
+            NetworkFault::Delay { millis } => {
+                bombadil_schema::NetworkFault::Delay { millis: *millis }
+            }
+        }
+    }
 }
 
 impl BrowserAction {
-    pub async fn apply(&self, page: &Page) -> Result<()> {
+    pub async fn apply(
+        &self,
+        page: &Page,
+        faults: &NetworkFaults,
+        tabs: &Tabs,
+        emulation: &Emulation,
+        network_conditions: &NetworkConditions,
+    ) -> Result<()> {
         match self {
             BrowserAction::Back => {
                 let history =
@@ -131,31 +499,53 @@ impl BrowserAction {
                     page.execute(input::InsertTextParams::new(char)).await?;
                 }
             }
-            BrowserAction::PressKey { code } => {
-                let build_params = |event_type| {
-                    if let Some(name) = key_name(*code) {
-                        input::DispatchKeyEventParams::builder()
-                            .r#type(event_type)
-                            .native_virtual_key_code(*code as i64)
-                            .windows_virtual_key_code(*code as i64)
-                            .code(name)
-                            .key(name)
-                            .unmodified_text("\r")
-                            .text("\r")
-                            .build()
-                            .map_err(|err| anyhow!(err))
-                    } else {
-                        bail!("unknown key with code: {:?}", code)
-                    }
-                };
-                page.execute(build_params(
+            BrowserAction::PressKey { code, modifiers } => {
+                let modifiers = Modifiers::from(modifiers);
+                let bits = modifiers.bits();
+                for modifier in modifiers.codes() {
+                    page.execute(key_event(
+                        input::DispatchKeyEventType::RawKeyDown,
+                        modifier,
+                        bits,
+                        None,
+                    )?)
+                    .await?;
+                }
+                // Chords are shortcuts rather than text input, so only plain
+                // key presses get a char event.
+                let text = modifiers.is_empty().then_some("\r");
+                page.execute(key_event(
                     input::DispatchKeyEventType::RawKeyDown,
+                    *code,
+                    bits,
+                    text,
                 )?)
                 .await?;
-                page.execute(build_params(input::DispatchKeyEventType::Char)?)
+                if text.is_some() {
+                    page.execute(key_event(
+                        input::DispatchKeyEventType::Char,
+                        *code,
+                        bits,
+                        text,
+                    )?)
                     .await?;
-                page.execute(build_params(input::DispatchKeyEventType::KeyUp)?)
+                }
+                page.execute(key_event(
+                    input::DispatchKeyEventType::KeyUp,
+                    *code,
+                    bits,
+                    text,
+                )?)
+                .await?;
+                for modifier in modifiers.codes().into_iter().rev() {
+                    page.execute(key_event(
+                        input::DispatchKeyEventType::KeyUp,
+                        modifier,
+                        bits,
+                        None,
+                    )?)
                     .await?;
+                }
             }
             BrowserAction::SetFileInputFiles { selector, files } => {
                 let document =
@@ -181,10 +571,309 @@ impl BrowserAction {
                 )
                 .await?;
             }
+            BrowserAction::InjectFault { url_pattern, fault } => {
+                faults.arm(page, url_pattern.clone(), fault.clone()).await?;
+            }
+            BrowserAction::GoOffline => {
+                network_conditions.set_online(&tabs.pages(), false).await?;
+            }
+            BrowserAction::GoOnline => {
+                network_conditions.set_online(&tabs.pages(), true).await?;
+            }
+            // The clock advances during the action's timeout.
+            BrowserAction::AdvanceClock { .. } => {}
+            BrowserAction::Resize { width, height } => {
+                emulation.set_viewport(page, *width, *height).await?;
+            }
+            BrowserAction::SwitchTab { index } => {
+                tabs.switch(*index as usize).await?;
+            }
+            BrowserAction::CloseTab { index } => {
+                tabs.close(*index as usize).await?;
+            }
+            BrowserAction::Tap { point, .. } => {
+                dispatch_touch(
+                    page,
+                    input::DispatchTouchEventType::TouchStart,
+                    &[*point],
+                )
+                .await?;
+                dispatch_touch(
+                    page,
+                    input::DispatchTouchEventType::TouchEnd,
+                    &[],
+                )
+                .await?;
+            }
+            BrowserAction::LongPress {
+                point,
+                duration_millis,
+                ..
+            } => {
+                dispatch_touch(
+                    page,
+                    input::DispatchTouchEventType::TouchStart,
+                    &[*point],
+                )
+                .await?;
+                sleep(Duration::from_millis(*duration_millis)).await;
+                dispatch_touch(
+                    page,
+                    input::DispatchTouchEventType::TouchEnd,
+                    &[],
+                )
+                .await?;
+            }
+            BrowserAction::Swipe { start, end } => {
+                touch_gesture(page, &[(*start, *end)]).await?;
+            }
+            BrowserAction::Drag { from, to, steps } => {
+                drag(page, *from, *to, *steps).await?;
+            }
+            BrowserAction::SelectOptions { selector, values } => {
+                call_function(
+                    page,
+                    SELECT_OPTIONS_FUNCTION,
+                    &[selector.as_str().into(), values.clone().into()],
+                )
+                .await?;
+            }
+            BrowserAction::SetInputValue { selector, value } => {
+                call_function(
+                    page,
+                    SET_INPUT_VALUE_FUNCTION,
+                    &[selector.as_str().into(), value.as_str().into()],
+                )
+                .await?;
+            }
+            BrowserAction::Hover { point, .. } => {
+                page.move_mouse((*point).into()).await?;
+            }
+            BrowserAction::ContextClick { point, .. } => {
+                // Like a real right click, the mouse moves with no button
+                // pressed, and the right button (2 in `event.buttons`) is
+                // held between the press and the release.
+                for (event_type, button, buttons, click_count) in [
+                    (
+                        input::DispatchMouseEventType::MouseMoved,
+                        input::MouseButton::None,
+                        0,
+                        0,
+                    ),
+                    (
+                        input::DispatchMouseEventType::MousePressed,
+                        input::MouseButton::Right,
+                        2,
+                        1,
+                    ),
+                    (
+                        input::DispatchMouseEventType::MouseReleased,
+                        input::MouseButton::Right,
+                        0,
+                        1,
+                    ),
+                ] {
+                    page.execute(
+                        input::DispatchMouseEventParams::builder()
+                            .r#type(event_type)
+                            .x(point.x)
+                            .y(point.y)
+                            .button(button)
+                            .buttons(buttons)
+                            .click_count(click_count)
+                            .build()
+                            .map_err(|err| anyhow!(err))?,
+                    )
+                    .await?;
+                }
+            }
+            BrowserAction::Pinch { origin, scale } => {
+                let finger = |direction: f64, span: f64| Point {
+                    x: origin.x + direction * span / 2.0,
+                    y: origin.y,
+                };
+                touch_gesture(
+                    page,
+                    &[
+                        (
+                            finger(-1.0, PINCH_SPAN),
+                            finger(-1.0, PINCH_SPAN * scale),
+                        ),
+                        (
+                            finger(1.0, PINCH_SPAN),
+                            finger(1.0, PINCH_SPAN * scale),
+                        ),
+                    ],
+                )
+                .await?;
+            }
+            BrowserAction::RunSetupScript => {
+                page.evaluate(RUN_SETUP_SCRIPT_EXPRESSION).await?;
+            }
         };
         Ok(())
     }
 
+    pub fn from_api(action: &bombadil_schema::BrowserAction) -> Self {
+        match action {
+            bombadil_schema::BrowserAction::Back => BrowserAction::Back,
+            bombadil_schema::BrowserAction::Forward => BrowserAction::Forward,
+            bombadil_schema::BrowserAction::Click {
+                name,
+                content,
+                point,
+            } => BrowserAction::Click {
+                name: name.clone(),
+                content: content.clone(),
+                point: Point::from_api(point),
+            },
+            bombadil_schema::BrowserAction::DoubleClick {
+                name,
+                content,
+                point,
+                delay_millis,
+            } => BrowserAction::DoubleClick {
+                name: name.clone(),
+                content: content.clone(),
+                point: Point::from_api(point),
+                delay_millis: *delay_millis,
+            },
+            bombadil_schema::BrowserAction::TypeText { text, delay_millis } => {
+                BrowserAction::TypeText {
+                    text: text.clone(),
+                    delay_millis: *delay_millis,
+                }
+            }
+            bombadil_schema::BrowserAction::PressKey { code, modifiers } => {
+                BrowserAction::PressKey {
+                    code: *code,
+                    modifiers: *modifiers,
+                }
+            }
+            bombadil_schema::BrowserAction::ScrollUp { origin, distance } => {
+                BrowserAction::ScrollUp {
+                    origin: Point::from_api(origin),
+                    distance: *distance,
+                }
+            }
+            bombadil_schema::BrowserAction::ScrollDown { origin, distance } => {
+                BrowserAction::ScrollDown {
+                    origin: Point::from_api(origin),
+                    distance: *distance,
+                }
+            }
+            bombadil_schema::BrowserAction::Reload => BrowserAction::Reload,
+            bombadil_schema::BrowserAction::Wait => BrowserAction::Wait,
+            bombadil_schema::BrowserAction::SetFileInputFiles {
+                selector,
+                files,
+            } => BrowserAction::SetFileInputFiles {
+                selector: selector.clone(),
+                files: files.clone(),
+            },
+            bombadil_schema::BrowserAction::InjectFault {
+                url_pattern,
+                fault,
+            } => BrowserAction::InjectFault {
+                url_pattern: url_pattern.clone(),
+                fault: NetworkFault::from_api(fault),
+            },
+            bombadil_schema::BrowserAction::GoOffline => {
+                BrowserAction::GoOffline
+            }
+            bombadil_schema::BrowserAction::GoOnline => BrowserAction::GoOnline,
+            bombadil_schema::BrowserAction::AdvanceClock { millis } => {
+                BrowserAction::AdvanceClock { millis: *millis }
+            }
+            bombadil_schema::BrowserAction::Resize { width, height } => {
+                BrowserAction::Resize {
+                    width: *width,
+                    height: *height,
+                }
+            }
+            bombadil_schema::BrowserAction::SwitchTab { index } => {
+                BrowserAction::SwitchTab { index: *index }
+            }
+            bombadil_schema::BrowserAction::CloseTab { index } => {
+                BrowserAction::CloseTab { index: *index }
+            }
+            bombadil_schema::BrowserAction::Tap {
+                name,
+                content,
+                point,
+            } => BrowserAction::Tap {
+                name: name.clone(),
+                content: content.clone(),
+                point: Point::from_api(point),
+            },
+            bombadil_schema::BrowserAction::LongPress {
+                name,
+                content,
+                point,
+                duration_millis,
+            } => BrowserAction::LongPress {
+                name: name.clone(),
+                content: content.clone(),
+                point: Point::from_api(point),
+                duration_millis: *duration_millis,
+            },
+            bombadil_schema::BrowserAction::Swipe { start, end } => {
+                BrowserAction::Swipe {
+                    start: Point::from_api(start),
+                    end: Point::from_api(end),
+                }
+            }
+            bombadil_schema::BrowserAction::Pinch { origin, scale } => {
+                BrowserAction::Pinch {
+                    origin: Point::from_api(origin),
+                    scale: *scale,
+                }
+            }
+            bombadil_schema::BrowserAction::Drag { from, to, steps } => {
+                BrowserAction::Drag {
+                    from: Point::from_api(from),
+                    to: Point::from_api(to),
+                    steps: *steps,
+                }
+            }
+            bombadil_schema::BrowserAction::SelectOptions {
+                selector,
+                values,
+            } => BrowserAction::SelectOptions {
+                selector: selector.clone(),
+                values: values.clone(),
+            },
+            bombadil_schema::BrowserAction::SetInputValue {
+                selector,
+                value,
+            } => BrowserAction::SetInputValue {
+                selector: selector.clone(),
+                value: value.clone(),
+            },
+            bombadil_schema::BrowserAction::Hover {
+                name,
+                content,
+                point,
+            } => BrowserAction::Hover {
+                name: name.clone(),
+                content: content.clone(),
+                point: Point::from_api(point),
+            },
+            bombadil_schema::BrowserAction::ContextClick {
+                name,
+                content,
+                point,
+            } => BrowserAction::ContextClick {
+                name: name.clone(),
+                content: content.clone(),
+                point: Point::from_api(point),
+            },
+            bombadil_schema::BrowserAction::RunSetupScript => {
+                BrowserAction::RunSetupScript
+            }
+        }
+    }
+
     pub fn to_api(&self) -> bombadil_schema::BrowserAction {
         match self {
             BrowserAction::Back => bombadil_schema::BrowserAction::Back,
@@ -215,8 +904,11 @@ impl BrowserAction {
                     delay_millis: *delay_millis,
                 }
             }
-            BrowserAction::PressKey { code } => {
-                bombadil_schema::BrowserAction::PressKey { code: *code }
+            BrowserAction::PressKey { code, modifiers } => {
+                bombadil_schema::BrowserAction::PressKey {
+                    code: *code,
+                    modifiers: *modifiers,
+                }
             }
             BrowserAction::ScrollUp { origin, distance } => {
                 bombadil_schema::BrowserAction::ScrollUp {
@@ -238,6 +930,103 @@ impl BrowserAction {
                     files: files.clone(),
                 }
             }
+            BrowserAction::InjectFault { url_pattern, fault } => {
+                bombadil_schema::BrowserAction::InjectFault {
+                    url_pattern: url_pattern.clone(),
+                    fault: fault.to_api(),
+                }
+            }
+            BrowserAction::GoOffline => {
+                bombadil_schema::BrowserAction::GoOffline
+            }
+            BrowserAction::GoOnline => bombadil_schema::BrowserAction::GoOnline,
+            BrowserAction::AdvanceClock { millis } => {
+                bombadil_schema::BrowserAction::AdvanceClock { millis: *millis }
+            }
+            BrowserAction::Resize { width, height } => {
+                bombadil_schema::BrowserAction::Resize {
+                    width: *width,
+                    height: *height,
+                }
+            }
+            BrowserAction::SwitchTab { index } => {
+                bombadil_schema::BrowserAction::SwitchTab { index: *index }
+            }
+            BrowserAction::CloseTab { index } => {
+                bombadil_schema::BrowserAction::CloseTab { index: *index }
+            }
+            BrowserAction::Tap {
+                name,
+                content,
+                point,
+            } => bombadil_schema::BrowserAction::Tap {
+                name: name.clone(),
+                content: content.clone(),
+                point: point.to_api(),
+            },
+            BrowserAction::LongPress {
+                name,
+                content,
+                point,
+                duration_millis,
+            } => bombadil_schema::BrowserAction::LongPress {
+                name: name.clone(),
+                content: content.clone(),
+                point: point.to_api(),
+                duration_millis: *duration_millis,
+            },
+            BrowserAction::Swipe { start, end } => {
+                bombadil_schema::BrowserAction::Swipe {
+                    start: start.to_api(),
+                    end: end.to_api(),
+                }
+            }
+            BrowserAction::Pinch { origin, scale } => {
+                bombadil_schema::BrowserAction::Pinch {
+                    origin: origin.to_api(),
+                    scale: *scale,
+                }
+            }
+            BrowserAction::Drag { from, to, steps } => {
+                bombadil_schema::BrowserAction::Drag {
+                    from: from.to_api(),
+                    to: to.to_api(),
+                    steps: *steps,
+                }
+            }
+            BrowserAction::SelectOptions { selector, values } => {
+                bombadil_schema::BrowserAction::SelectOptions {
+                    selector: selector.clone(),
+                    values: values.clone(),
+                }
+            }
+            BrowserAction::SetInputValue { selector, value } => {
+                bombadil_schema::BrowserAction::SetInputValue {
+                    selector: selector.clone(),
+                    value: value.clone(),
+                }
+            }
+            BrowserAction::Hover {
+                name,
+                content,
+                point,
+            } => bombadil_schema::BrowserAction::Hover {
+                name: name.clone(),
+                content: content.clone(),
+                point: point.to_api(),
+            },
+            BrowserAction::ContextClick {
+                name,
+                content,
+                point,
+            } => bombadil_schema::BrowserAction::ContextClick {
+                name: name.clone(),
+                content: content.clone(),
+                point: point.to_api(),
+            },
+            BrowserAction::RunSetupScript => {
+                bombadil_schema::BrowserAction::RunSetupScript
+            }
         }
     }
 }
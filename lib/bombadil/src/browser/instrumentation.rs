@@ -9,41 +9,211 @@ use log;
 use oxc::span::SourceType;
 use serde_json as json;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::spawn;
+use tokio::time::sleep;
 
+use crate::browser::actions::NetworkFault;
 use crate::instrumentation;
 use crate::instrumentation::InstrumentationConfig;
 use crate::instrumentation::source_id::SourceId;
 
+/// Faults armed by `InjectFault` actions, each waiting for the first request
+/// matching its URL pattern.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkFaults {
+    armed: Arc<Mutex<Vec<(String, NetworkFault)>>>,
+}
+
+impl NetworkFaults {
+    pub fn new() -> Self {
+        NetworkFaults::default()
+    }
+
+    pub async fn arm(
+        &self,
+        page: &Page,
+        url_pattern: String,
+        fault: NetworkFault,
+    ) -> Result<()> {
+        let url_patterns = {
+            let mut armed = self.armed.lock().expect("faults lock poisoned");
+            armed.push((url_pattern, fault));
+            url_patterns(&armed)
+        };
+        enable_interception(page, &url_patterns).await
+    }
+
+    /// Disarms all faults, e.g. when resetting the browser.
+    pub async fn clear(&self, page: &Page) -> Result<()> {
+        self.armed.lock().expect("faults lock poisoned").clear();
+        enable_interception(page, &[]).await
+    }
+
+    /// Removes and returns the first fault matching the URL, along with the
+    /// URL patterns of the faults still armed.
+    fn take(&self, url: &str) -> Option<(NetworkFault, Vec<String>)> {
+        let mut armed = self.armed.lock().expect("faults lock poisoned");
+        let index = armed.iter().position(|(url_pattern, _)| {
+            matches_url_pattern(url_pattern, url)
+        })?;
+        let (_, fault) = armed.remove(index);
+        Some((fault, url_patterns(&armed)))
+    }
+}
+
+fn url_patterns(armed: &[(String, NetworkFault)]) -> Vec<String> {
+    armed
+        .iter()
+        .map(|(url_pattern, _)| url_pattern.clone())
+        .collect()
+}
+
+/// Matches a URL against a pattern where `*` matches any sequence of
+/// characters and `?` matches a single character, like CDP request patterns.
+fn matches_url_pattern(pattern: &str, url: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let url: Vec<char> = url.chars().collect();
+    let (mut p, mut u) = (0, 0);
+    // Where to resume after the last `*`, if the match so far fails.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while u < url.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, u));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == url[u] => {
+                p += 1;
+                u += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_u)) => {
+                    p = star_p;
+                    u = star_u + 1;
+                    backtrack = Some((star_p, star_u + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Intercepts scripts and documents after their responses, for
+/// instrumentation, and requests matching armed faults before they're sent.
+async fn enable_interception(
+    page: &Page,
+    url_patterns: &[String],
+) -> Result<()> {
+    let mut builder = fetch::EnableParams::builder()
+        .pattern(
+            fetch::RequestPattern::builder()
+                .request_stage(fetch::RequestStage::Response)
+                .resource_type(network::ResourceType::Script)
+                .build(),
+        )
+        .pattern(
+            fetch::RequestPattern::builder()
+                .request_stage(fetch::RequestStage::Response)
+                .resource_type(network::ResourceType::Document)
+                .build(),
+        );
+    for url_pattern in url_patterns {
+        builder = builder.pattern(
+            fetch::RequestPattern::builder()
+                .url_pattern(url_pattern.clone())
+                .request_stage(fetch::RequestStage::Request)
+                .build(),
+        );
+    }
+    page.execute(builder.build())
+        .await
+        .context("failed enabling request interception")?;
+    Ok(())
+}
+
+async fn inject_fault(
+    page: &Arc<Page>,
+    faults: &NetworkFaults,
+    event: &fetch::EventRequestPaused,
+) -> Result<()> {
+    let continue_params = fetch::ContinueRequestParams::builder()
+        .request_id(event.request_id.clone())
+        .build()
+        .map_err(|error| {
+            anyhow!("failed building ContinueRequestParams: {}", error)
+        })?;
+    let Some((fault, url_patterns)) = faults.take(&event.request.url) else {
+        page.execute(continue_params)
+            .await
+            .context("failed continuing request")?;
+        return Ok(());
+    };
+    enable_interception(page, &url_patterns).await?;
+
+    log::info!("injecting {:?} in request to {}", fault, event.request.url);
+    match fault {
+        NetworkFault::Fail => {
+            page.execute(fetch::FailRequestParams::new(
+                event.request_id.clone(),
+                network::ErrorReason::Failed,
+            ))
+            .await
+            .context("failed failing request")?;
+        }
+        NetworkFault::Status { code } => {
+            page.execute(
+                fetch::FulfillRequestParams::builder()
+                    .request_id(event.request_id.clone())
+                    .response_code(code as i64)
+                    .body(BASE64_STANDARD.encode(""))
+                    .build()
+                    .map_err(|error| {
+                        anyhow!(
+                            "failed building FulfillRequestParams: {}",
+                            error
+                        )
+                    })?,
+            )
+            .await
+            .context("failed fulfilling request")?;
+        }
+        NetworkFault::Delay { millis } => {
+            // Other requests are intercepted in the meantime.
+            let page = page.clone();
+            spawn(async move {
+                sleep(Duration::from_millis(millis)).await;
+                if let Err(error) = page.execute(continue_params).await {
+                    log::warn!("failed continuing delayed request: {error}");
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
 pub async fn instrument_js_coverage(
     page: Arc<Page>,
     config: InstrumentationConfig,
+    faults: NetworkFaults,
 ) -> Result<()> {
-    page.execute(
-        fetch::EnableParams::builder()
-            .pattern(
-                fetch::RequestPattern::builder()
-                    .request_stage(fetch::RequestStage::Response)
-                    .resource_type(network::ResourceType::Script)
-                    .build(),
-            )
-            .pattern(
-                fetch::RequestPattern::builder()
-                    .request_stage(fetch::RequestStage::Response)
-                    .resource_type(network::ResourceType::Document)
-                    .build(),
-            )
-            .build(),
-    )
-    .await
-    .context("failed enabling request interception")?;
+    enable_interception(&page, &[]).await?;
 
     let mut events = page.event_listener::<fetch::EventRequestPaused>().await?;
 
     let _handle = spawn(async move {
         let intercept =
             async |event: &fetch::EventRequestPaused| -> Result<()> {
+                // Requests paused before being sent are only intercepted for
+                // injecting faults.
+                if event.response_status_code.is_none()
+                    && event.response_error_reason.is_none()
+                {
+                    return inject_fault(&page, &faults, event).await;
+                }
+
                 // Any non-200 upstream response is forwarded as-is.
                 if let Some(status) = event.response_status_code
                     && status != 200
@@ -242,3 +412,41 @@ fn source_id(headers: HashMap<String, String>, body: &str) -> SourceId {
         SourceId::hash(body)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_pattern_wildcards() {
+        let url = "http://localhost:8080/api/items?page=2";
+        assert!(matches_url_pattern("*", url));
+        assert!(matches_url_pattern("*/api/*", url));
+        assert!(matches_url_pattern("http://localhost:????/api/items*", url));
+        assert!(matches_url_pattern(url, url));
+        assert!(!matches_url_pattern("*/api/users*", url));
+        assert!(!matches_url_pattern("http://localhost:8080/api", url));
+        assert!(!matches_url_pattern("", url));
+    }
+
+    #[test]
+    fn test_faults_are_taken_once_in_order() {
+        let faults = NetworkFaults::new();
+        faults.armed.lock().unwrap().extend([
+            ("*/a".to_string(), NetworkFault::Fail),
+            ("*/b".to_string(), NetworkFault::Status { code: 503 }),
+            ("*".to_string(), NetworkFault::Delay { millis: 10 }),
+        ]);
+
+        let (fault, url_patterns) = faults.take("http://x/b").unwrap();
+        assert_eq!(fault, NetworkFault::Status { code: 503 });
+        assert_eq!(url_patterns, vec!["*/a", "*"]);
+
+        let (fault, _) = faults.take("http://x/a").unwrap();
+        assert_eq!(fault, NetworkFault::Fail);
+        let (fault, url_patterns) = faults.take("http://x/a").unwrap();
+        assert_eq!(fault, NetworkFault::Delay { millis: 10 });
+        assert!(url_patterns.is_empty());
+        assert!(faults.take("http://x/a").is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chromiumoxide::Page;
+use chromiumoxide::cdp::browser_protocol::network;
+use serde::{Deserialize, Serialize};
+
+use crate::browser::state::Connectivity;
+
+/// Emulated network latency and bandwidth, applied to every page for the
+/// whole test.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Throttling {
+    /// Added to the round trip of every request.
+    pub latency_millis: f64,
+    pub download_kbps: f64,
+    pub upload_kbps: f64,
+}
+
+impl Throttling {
+    /// Named presets, matching the ones of the Chrome DevTools network
+    /// panel.
+    pub const PRESETS: &[(&str, Throttling)] = &[
+        (
+            "slow-3g",
+            Throttling {
+                latency_millis: 2000.0,
+                download_kbps: 400.0,
+                upload_kbps: 400.0,
+            },
+        ),
+        (
+            "fast-3g",
+            Throttling {
+                latency_millis: 562.5,
+                download_kbps: 1440.0,
+                upload_kbps: 675.0,
+            },
+        ),
+        (
+            "fast-4g",
+            Throttling {
+                latency_millis: 165.0,
+                download_kbps: 8100.0,
+                upload_kbps: 1350.0,
+            },
+        ),
+    ];
+
+    pub fn preset(name: &str) -> Option<Throttling> {
+        Throttling::PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, throttling)| *throttling)
+    }
+}
+
+/// Whether pages are online, as toggled by `GoOffline` and `GoOnline`
+/// actions, and how their network is throttled.
+#[derive(Clone, Debug)]
+pub struct NetworkConditions {
+    throttling: Option<Throttling>,
+    online: Arc<Mutex<bool>>,
+}
+
+impl NetworkConditions {
+    pub fn new(throttling: Option<Throttling>) -> Self {
+        NetworkConditions {
+            throttling,
+            online: Arc::new(Mutex::new(true)),
+        }
+    }
+
+    pub fn connectivity(&self) -> Connectivity {
+        Connectivity {
+            online: *self
+                .online
+                .lock()
+                .expect("network conditions lock poisoned"),
+            throttling: self.throttling,
+        }
+    }
+
+    /// Emulates the current conditions in a page, like a newly opened tab.
+    pub async fn attach(&self, page: &Page) -> Result<()> {
+        let connectivity = self.connectivity();
+        if connectivity.online && connectivity.throttling.is_none() {
+            return Ok(());
+        }
+        emulate(page, &connectivity).await
+    }
+
+    /// Takes the pages offline or back online.
+    pub async fn set_online(
+        &self,
+        pages: &[Arc<Page>],
+        online: bool,
+    ) -> Result<()> {
+        *self
+            .online
+            .lock()
+            .expect("network conditions lock poisoned") = online;
+        let connectivity = self.connectivity();
+        for page in pages {
+            emulate(page, &connectivity).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn emulate(page: &Page, connectivity: &Connectivity) -> Result<()> {
+    // Throughputs are in bytes per second, where -1 disables throttling.
+    let (latency, download_throughput, upload_throughput) =
+        match connectivity.throttling {
+            Some(throttling) => (
+                throttling.latency_millis,
+                throttling.download_kbps * 1000.0 / 8.0,
+                throttling.upload_kbps * 1000.0 / 8.0,
+            ),
+            None => (0.0, -1.0, -1.0),
+        };
+    page.execute(network::EmulateNetworkConditionsParams::new(
+        !connectivity.online,
+        latency,
+        download_throughput,
+        upload_throughput,
+    ))
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_found_by_name() {
+        assert_eq!(
+            Throttling::preset("slow-3g").map(|t| t.latency_millis),
+            Some(2000.0)
+        );
+        assert!(Throttling::preset("fast-4g").is_some());
+        assert!(Throttling::preset("dial-up").is_none());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::instrumentation::js::{
     EDGE_MAP_SIZE, EDGES_CURRENT, EDGES_PREVIOUS, NAMESPACE,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use chromiumoxide::{
     Page,
     cdp::{
@@ -14,12 +14,14 @@ use chromiumoxide::{
 };
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json as json;
-use std::{sync::Arc, time::SystemTime};
+use std::{collections::BTreeMap, sync::Arc, time::SystemTime};
 use url::Url;
 
+use crate::browser::InnerStateShared;
 use crate::browser::evaluation::{
     evaluate_expression_in_debugger, evaluate_function_call_in_debugger,
 };
+use crate::browser::network_conditions::Throttling;
 
 #[derive(Clone, Debug)]
 pub struct BrowserState {
@@ -31,7 +33,20 @@ pub struct BrowserState {
     pub title: String,
     pub content_type: String,
     pub console_entries: Vec<ConsoleEntry>,
+    pub network_requests: Vec<NetworkRequest>,
     pub navigation_history: NavigationHistory,
+    pub tabs: Vec<Tab>,
+    pub connectivity: Connectivity,
+    /// Whether touch input is emulated instead of a mouse, as with
+    /// `--mobile`.
+    pub touch: bool,
+    pub storage: Storage,
+    /// Only captured when enabled in the browser options.
+    pub accessibility_tree: Option<Vec<AccessibilityNode>>,
+    /// How many actions had finished applying when the state was captured,
+    /// telling states that follow an action apart from ones captured before
+    /// it was applied, like when a page finishes loading.
+    pub applied_actions: u64,
     pub exceptions: Vec<Exception>,
     pub transition_hash: Option<u64>,
     pub coverage: Coverage,
@@ -103,6 +118,134 @@ pub enum ConsoleEntryLevel {
     Error,
 }
 
+/// An open page, in the order pages were opened.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tab {
+    pub url: String,
+    pub title: String,
+    pub active: bool,
+}
+
+/// Whether the pages are online, and how their network is throttled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Connectivity {
+    pub online: bool,
+    pub throttling: Option<Throttling>,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Connectivity {
+            online: true,
+            throttling: None,
+        }
+    }
+}
+
+/// The cookies visible to the active page, and the web storage of its
+/// origin.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Storage {
+    pub cookies: Vec<Cookie>,
+    pub local_storage: BTreeMap<String, String>,
+    pub session_storage: BTreeMap<String, String>,
+    pub indexed_db_databases: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Seconds since the epoch, or `None` for session cookies.
+    pub expires: Option<f64>,
+    pub http_only: bool,
+    pub secure: bool,
+    pub same_site: Option<String>,
+}
+
+/// A node of the accessibility tree of the active page, as exposed to
+/// assistive technology. Nodes refer to their parent and children by id.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilityNode {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub child_ids: Vec<String>,
+    pub role: Option<String>,
+    /// The accessible name, computed from labels, contents and ARIA
+    /// attributes.
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub value: Option<String>,
+    /// Ignored nodes, like hidden or purely presentational ones, aren't
+    /// exposed to assistive technology.
+    pub ignored: bool,
+    pub ignored_reasons: Vec<String>,
+    /// Properties like `focusable`, `focused`, `disabled` or `checked`, by
+    /// name.
+    pub properties: BTreeMap<String, json::Value>,
+}
+
+impl Storage {
+    pub fn to_api(&self) -> bombadil_schema::Storage {
+        bombadil_schema::Storage {
+            cookies: self
+                .cookies
+                .iter()
+                .map(|cookie| bombadil_schema::Cookie {
+                    name: cookie.name.clone(),
+                    value: cookie.value.clone(),
+                    domain: cookie.domain.clone(),
+                    path: cookie.path.clone(),
+                    expires: cookie.expires,
+                    http_only: cookie.http_only,
+                    secure: cookie.secure,
+                    same_site: cookie.same_site.clone(),
+                })
+                .collect(),
+            local_storage: self.local_storage.clone(),
+            session_storage: self.session_storage.clone(),
+            indexed_db_databases: self.indexed_db_databases.clone(),
+        }
+    }
+}
+
+/// A network request made by the page, recorded once it has finished or
+/// failed.
+#[derive(Clone, Debug, Serialize)]
+pub struct NetworkRequest {
+    /// When the request was sent.
+    pub timestamp: SystemTime,
+    pub url: String,
+    pub method: String,
+    /// The kind of resource requested, e.g. "Document", "Fetch", "Script".
+    pub resource_type: Option<String>,
+    /// The HTTP status code, if a response was received.
+    pub status: Option<u16>,
+    /// Time from sending the request until it finished or failed.
+    pub duration_millis: f64,
+    /// Why the request failed, e.g. "net::ERR_CONNECTION_REFUSED".
+    pub failure: Option<String>,
+}
+
+impl NetworkRequest {
+    pub fn to_api(&self) -> bombadil_schema::NetworkRequest {
+        bombadil_schema::NetworkRequest {
+            timestamp: bombadil_schema::Time::from_system_time(self.timestamp),
+            url: self.url.clone(),
+            method: self.method.clone(),
+            resource_type: self.resource_type.clone(),
+            status: self.status,
+            duration_millis: self.duration_millis,
+            failure: self.failure.clone(),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum ScreenshotFormat {
     Webp,
@@ -209,10 +352,24 @@ impl BrowserState {
     pub(crate) async fn current(
         page: Arc<Page>,
         call_frame_id: &CallFrameId,
-        console_entries: Vec<ConsoleEntry>,
-        exceptions: Vec<Exception>,
-        screenshot: Screenshot,
+        shared: InnerStateShared,
     ) -> Result<Self> {
+        let InnerStateShared {
+            console_entries,
+            network_requests,
+            exceptions,
+            screenshot,
+            storage,
+            accessibility_tree,
+            tabs,
+            connectivity,
+            touch,
+            applied_actions,
+            ..
+        } = shared;
+        let screenshot = screenshot
+            .ok_or(anyhow!("no screenshot available for state capture"))?;
+
         log::trace!("BrowserState::current: evaluating url");
         let url = Url::parse(
             &evaluate_expression_in_debugger::<String>(
@@ -388,7 +545,14 @@ impl BrowserState {
             title,
             content_type,
             console_entries,
+            network_requests,
             navigation_history,
+            tabs,
+            connectivity,
+            touch,
+            storage,
+            accessibility_tree,
+            applied_actions,
             exceptions,
             coverage: Coverage { edges_new },
             transition_hash,
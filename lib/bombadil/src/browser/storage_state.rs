@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chromiumoxide::Page;
+use chromiumoxide::cdp::browser_protocol::{network, page};
+use chromiumoxide::cdp::js_protocol::runtime;
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+use url::Url;
+
+use crate::browser::state::{Cookie, Storage};
+
+/// Reads local storage, session storage and the IndexedDB database names of
+/// the page's origin, leaving out whatever the origin can't access.
+const WEB_STORAGE_EXPRESSION: &str = r#"(async () => {
+  const items = (storage) => {
+    const result = {};
+    for (let i = 0; i < storage.length; i++) {
+      const key = storage.key(i);
+      result[key] = storage.getItem(key);
+    }
+    return result;
+  };
+  const attempt = async (read, fallback) => {
+    try {
+      return await read();
+    } catch {
+      return fallback;
+    }
+  };
+  return {
+    localStorage: await attempt(() => items(window.localStorage), {}),
+    sessionStorage: await attempt(() => items(window.sessionStorage), {}),
+    indexedDbDatabases: await attempt(
+      async () => (await indexedDB.databases()).map((database) => database.name),
+      [],
+    ),
+  };
+})()"#;
+
+/// Sets local storage items for the origin of the document, if there are
+/// any, before the document's own scripts run.
+const LOCAL_STORAGE_SCRIPT: &str = r#"((origins) => {
+  const items = origins[window.location.origin];
+  if (!items) return;
+  for (const { name, value } of items) {
+    window.localStorage.setItem(name, value);
+  }
+})"#;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WebStorage {
+    local_storage: BTreeMap<String, String>,
+    session_storage: BTreeMap<String, String>,
+    indexed_db_databases: Vec<String>,
+}
+
+/// Captures the cookies visible to the page, including HttpOnly ones, and
+/// the web storage of its origin.
+pub async fn capture_storage(page: &Page) -> Result<Storage> {
+    let cookies = page
+        .execute(network::GetCookiesParams::default())
+        .await?
+        .result
+        .cookies
+        .iter()
+        .map(|cookie| Cookie {
+            name: cookie.name.clone(),
+            value: cookie.value.clone(),
+            domain: cookie.domain.clone(),
+            path: cookie.path.clone(),
+            expires: (!cookie.session).then_some(cookie.expires),
+            http_only: cookie.http_only,
+            secure: cookie.secure,
+            same_site: cookie
+                .same_site
+                .as_ref()
+                .map(|same_site| same_site.as_ref().to_string()),
+        })
+        .collect();
+
+    let result = page
+        .execute(
+            runtime::EvaluateParams::builder()
+                .expression(WEB_STORAGE_EXPRESSION)
+                .await_promise(true)
+                .return_by_value(true)
+                .build()
+                .map_err(|err| {
+                    anyhow!(err).context("build EvaluateParams failed")
+                })?,
+        )
+        .await?
+        .result;
+    if let Some(exception) = result.exception_details {
+        bail!("reading web storage failed: {}", exception.text);
+    }
+    let web_storage: WebStorage = json::from_value(
+        result
+            .result
+            .value
+            .ok_or(anyhow!("reading web storage returned no value"))?,
+    )?;
+
+    Ok(Storage {
+        cookies,
+        local_storage: web_storage.local_storage,
+        session_storage: web_storage.session_storage,
+        indexed_db_databases: web_storage.indexed_db_databases,
+    })
+}
+
+/// Cookies and local storage to start every episode with, e.g. to explore
+/// as a logged-in user. Uses the format of Playwright's storage state files.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSeed {
+    #[serde(default)]
+    pub cookies: Vec<SeedCookie>,
+    #[serde(default)]
+    pub origins: Vec<SeedOrigin>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedCookie {
+    pub name: String,
+    pub value: String,
+    /// The cookie is set for the test's origin when neither `url` nor
+    /// `domain` is given.
+    pub url: Option<String>,
+    pub domain: Option<String>,
+    pub path: Option<String>,
+    /// Seconds since the epoch, where -1 (or none) makes a session cookie.
+    pub expires: Option<f64>,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+    /// One of "Strict", "Lax" or "None".
+    pub same_site: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedOrigin {
+    pub origin: String,
+    #[serde(default)]
+    pub local_storage: Vec<SeedItem>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeedItem {
+    pub name: String,
+    pub value: String,
+}
+
+impl StorageSeed {
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let seed: StorageSeed =
+            json::from_str(&contents).with_context(|| {
+                format!("invalid storage state {}", path.display())
+            })?;
+        // Fail early on invalid origins, rather than when seeding.
+        seed.local_storage_script()?;
+        Ok(seed)
+    }
+
+    pub async fn set_cookies(&self, page: &Page, origin: &Url) -> Result<()> {
+        if self.cookies.is_empty() {
+            return Ok(());
+        }
+        let cookies = self
+            .cookies
+            .iter()
+            .map(|cookie| cookie.to_param(origin))
+            .collect::<Result<Vec<_>>>()?;
+        page.execute(network::SetCookiesParams::new(cookies))
+            .await?;
+        Ok(())
+    }
+
+    /// Sets the cookies, and adds a script setting local storage in new
+    /// documents, returned so that it can be removed once the origin has
+    /// loaded.
+    pub async fn apply(
+        &self,
+        page: &Page,
+        origin: &Url,
+    ) -> Result<Option<page::ScriptIdentifier>> {
+        self.set_cookies(page, origin).await?;
+        let Some(source) = self.local_storage_script()? else {
+            return Ok(None);
+        };
+        let identifier = page
+            .execute(page::AddScriptToEvaluateOnNewDocumentParams::new(source))
+            .await?
+            .result
+            .identifier;
+        Ok(Some(identifier))
+    }
+
+    /// A script setting the local storage items of a document's origin, or
+    /// `None` if there are none to set.
+    pub fn local_storage_script(&self) -> Result<Option<String>> {
+        let mut origins = BTreeMap::<String, Vec<SeedItem>>::new();
+        for seed_origin in &self.origins {
+            if seed_origin.local_storage.is_empty() {
+                continue;
+            }
+            let origin = Url::parse(&seed_origin.origin)
+                .with_context(|| {
+                    format!("invalid origin {:?}", seed_origin.origin)
+                })?
+                .origin()
+                .ascii_serialization();
+            origins
+                .entry(origin)
+                .or_default()
+                .extend(seed_origin.local_storage.iter().cloned());
+        }
+        if origins.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(format!(
+            "{}({});",
+            LOCAL_STORAGE_SCRIPT,
+            json::to_string(&origins)?
+        )))
+    }
+}
+
+impl SeedCookie {
+    fn to_param(&self, origin: &Url) -> Result<network::CookieParam> {
+        let mut builder = network::CookieParam::builder()
+            .name(&self.name)
+            .value(&self.value)
+            .http_only(self.http_only)
+            .secure(self.secure);
+        if self.url.is_none() && self.domain.is_none() {
+            builder = builder.url(origin.as_str());
+        }
+        if let Some(url) = &self.url {
+            builder = builder.url(url);
+        }
+        if let Some(domain) = &self.domain {
+            builder = builder.domain(domain);
+        }
+        if let Some(path) = &self.path {
+            builder = builder.path(path);
+        }
+        if let Some(expires) = self.expires.filter(|expires| *expires >= 0.0) {
+            builder = builder.expires(network::TimeSinceEpoch::new(expires));
+        }
+        if let Some(same_site) = &self.same_site {
+            builder = builder.same_site(
+                same_site
+                    .parse::<network::CookieSameSite>()
+                    .map_err(|err| anyhow!(err))
+                    .with_context(|| {
+                        format!("invalid sameSite for cookie {:?}", self.name)
+                    })?,
+            );
+        }
+        builder.build().map_err(|err| {
+            anyhow!(err).context(format!("invalid cookie {:?}", self.name))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_storage_script_groups_items_by_origin() {
+        let seed: StorageSeed = json::from_str(
+            r#"{
+                "origins": [
+                    {
+                        "origin": "http://localhost:3000/",
+                        "localStorage": [{ "name": "token", "value": "abc" }]
+                    },
+                    { "origin": "https://example.com", "localStorage": [] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let script = seed.local_storage_script().unwrap().unwrap();
+        assert!(script.ends_with(
+            r#"({"http://localhost:3000":[{"name":"token","value":"abc"}]});"#
+        ));
+    }
+
+    #[test]
+    fn test_local_storage_script_is_none_without_items() {
+        let seed: StorageSeed = json::from_str(r#"{ "cookies": [] }"#).unwrap();
+        assert!(seed.local_storage_script().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_local_storage_script_rejects_invalid_origins() {
+        let seed: StorageSeed = json::from_str(
+            r#"{
+                "origins": [
+                    {
+                        "origin": "not an origin",
+                        "localStorage": [{ "name": "a", "value": "b" }]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert!(seed.local_storage_script().is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Result, bail};
+use chromiumoxide::Page;
+use chromiumoxide::cdp::browser_protocol::page::{self, FrameId};
+use chromiumoxide::cdp::browser_protocol::target::{self, TargetId};
+
+use crate::browser::state::Tab;
+
+#[derive(Clone, Debug)]
+struct OpenTab {
+    page: Arc<Page>,
+    frame_id: FrameId,
+}
+
+#[derive(Debug)]
+struct TabsInner {
+    tabs: Vec<OpenTab>,
+    active: usize,
+}
+
+/// The pages open in the browser, in the order they were opened, and which
+/// of them is active: the one actions are applied to and states are
+/// captured from.
+#[derive(Clone, Debug)]
+pub struct Tabs {
+    inner: Arc<Mutex<TabsInner>>,
+}
+
+impl Tabs {
+    pub fn new(page: Arc<Page>, frame_id: FrameId) -> Self {
+        Tabs {
+            inner: Arc::new(Mutex::new(TabsInner {
+                tabs: vec![OpenTab { page, frame_id }],
+                active: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TabsInner> {
+        self.inner.lock().expect("tabs lock poisoned")
+    }
+
+    pub fn active(&self) -> Arc<Page> {
+        let inner = self.lock();
+        inner.tabs[inner.active].page.clone()
+    }
+
+    /// The main frame of the active page.
+    pub fn active_frame_id(&self) -> FrameId {
+        let inner = self.lock();
+        inner.tabs[inner.active].frame_id.clone()
+    }
+
+    pub fn is_active(&self, target_id: &TargetId) -> bool {
+        let inner = self.lock();
+        inner.tabs[inner.active].page.target_id() == target_id
+    }
+
+    pub fn pages(&self) -> Vec<Arc<Page>> {
+        self.lock()
+            .tabs
+            .iter()
+            .map(|tab| tab.page.clone())
+            .collect()
+    }
+
+    /// Adds a newly opened page, without making it active.
+    pub fn open(&self, page: Arc<Page>, frame_id: FrameId) {
+        self.lock().tabs.push(OpenTab { page, frame_id });
+    }
+
+    /// Makes the most recently opened page active.
+    pub async fn switch_to_last(&self) -> Result<()> {
+        let last = self.lock().tabs.len() - 1;
+        self.switch(last).await
+    }
+
+    pub async fn switch(&self, index: usize) -> Result<()> {
+        let page = {
+            let mut inner = self.lock();
+            if index >= inner.tabs.len() {
+                bail!(
+                    "no tab with index {}, there are {} open",
+                    index,
+                    inner.tabs.len()
+                );
+            }
+            inner.active = index;
+            inner.tabs[index].page.clone()
+        };
+        page.execute(page::BringToFrontParams::default()).await?;
+        Ok(())
+    }
+
+    /// Closes the page with the given index. If it was the active one, the
+    /// page opened before it becomes active.
+    pub async fn close(&self, index: usize) -> Result<()> {
+        let closed = {
+            let mut inner = self.lock();
+            if index >= inner.tabs.len() {
+                bail!(
+                    "no tab with index {}, there are {} open",
+                    index,
+                    inner.tabs.len()
+                );
+            }
+            if inner.tabs.len() == 1 {
+                bail!("can't close the last open tab");
+            }
+            let closed = inner.tabs.remove(index);
+            if inner.active >= index {
+                inner.active = inner.active.saturating_sub(1);
+            }
+            closed.page
+        };
+        let active = self.active();
+        active.execute(page::BringToFrontParams::default()).await?;
+        active
+            .execute(target::CloseTargetParams::new(closed.target_id().clone()))
+            .await?;
+        Ok(())
+    }
+
+    /// Closes all pages but the first, making it active.
+    pub async fn close_others(&self) -> Result<()> {
+        let closed = {
+            let mut inner = self.lock();
+            inner.active = 0;
+            inner.tabs.split_off(1)
+        };
+        if closed.is_empty() {
+            return Ok(());
+        }
+        let active = self.active();
+        active.execute(page::BringToFrontParams::default()).await?;
+        for tab in closed {
+            active
+                .execute(target::CloseTargetParams::new(
+                    tab.page.target_id().clone(),
+                ))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Forgets a page that was closed, like a popup closing itself. Returns
+    /// whether it was the active page, in which case the page opened before
+    /// it becomes active, or `None` if the page wasn't tracked or it was the
+    /// only one.
+    pub fn remove(&self, target_id: &TargetId) -> Option<bool> {
+        let mut inner = self.lock();
+        let index = inner
+            .tabs
+            .iter()
+            .position(|tab| tab.page.target_id() == target_id)?;
+        if inner.tabs.len() == 1 {
+            return None;
+        }
+        inner.tabs.remove(index);
+        let was_active = inner.active == index;
+        if inner.active >= index {
+            inner.active = inner.active.saturating_sub(1);
+        }
+        Some(was_active)
+    }
+
+    /// Describes the open pages, without evaluating anything in them, as the
+    /// active one is paused when states are captured.
+    pub async fn describe(&self) -> Result<Vec<Tab>> {
+        let (pages, active) = {
+            let inner = self.lock();
+            let pages = inner
+                .tabs
+                .iter()
+                .map(|tab| tab.page.clone())
+                .collect::<Vec<_>>();
+            (pages, inner.active)
+        };
+        let targets = pages[active]
+            .execute(target::GetTargetsParams::default())
+            .await?
+            .result
+            .target_infos;
+        Ok(pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let info = targets
+                    .iter()
+                    .find(|info| &info.target_id == page.target_id());
+                Tab {
+                    url: info.map(|info| info.url.clone()).unwrap_or_default(),
+                    title: info
+                        .map(|info| info.title.clone())
+                        .unwrap_or_default(),
+                    active: index == active,
+                }
+            })
+            .collect())
+    }
+}
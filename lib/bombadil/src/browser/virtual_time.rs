@@ -0,0 +1,136 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+use chromiumoxide::Page;
+use chromiumoxide::cdp::browser_protocol::{emulation, network};
+use futures::StreamExt;
+
+/// How long to wait for the browser to run through an advance of virtual
+/// time before giving up on it.
+const ADVANCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The page's virtual clock. Under virtual time, timers in the page only fire
+/// when Bombadil advances the clock, and `Date.now()` reports virtual time, so
+/// timer-driven behaviour doesn't depend on machine load.
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    now: Arc<Mutex<SystemTime>>,
+    last_timestamp: Arc<Mutex<Option<SystemTime>>>,
+}
+
+impl VirtualClock {
+    /// Pauses virtual time in the page, starting it at the current wall-clock
+    /// time.
+    pub async fn start(page: &Page) -> Result<Self> {
+        let now = SystemTime::now();
+        page.execute(
+            emulation::SetVirtualTimePolicyParams::builder()
+                .policy(emulation::VirtualTimePolicy::Pause)
+                .initial_virtual_time(network::TimeSinceEpoch::new(
+                    now.duration_since(UNIX_EPOCH)?.as_secs_f64(),
+                ))
+                .build()
+                .map_err(|err| {
+                    anyhow!(err)
+                        .context("build SetVirtualTimePolicyParams failed")
+                })?,
+        )
+        .await?;
+        Ok(VirtualClock {
+            now: Arc::new(Mutex::new(now)),
+            last_timestamp: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Pauses virtual time in another page, like a newly opened tab,
+    /// starting it at the clock's current time.
+    pub async fn attach(&self, page: &Page) -> Result<()> {
+        page.execute(
+            emulation::SetVirtualTimePolicyParams::builder()
+                .policy(emulation::VirtualTimePolicy::Pause)
+                .initial_virtual_time(network::TimeSinceEpoch::new(
+                    self.now().duration_since(UNIX_EPOCH)?.as_secs_f64(),
+                ))
+                .build()
+                .map_err(|err| {
+                    anyhow!(err)
+                        .context("build SetVirtualTimePolicyParams failed")
+                })?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub fn now(&self) -> SystemTime {
+        *self.now.lock().expect("virtual clock lock poisoned")
+    }
+
+    /// A timestamp for a captured state: the current time, or a microsecond
+    /// after the previous timestamp if the clock hasn't moved since, as
+    /// states are captured without advancing it (like after a reset), and
+    /// must still be ordered and distinct.
+    pub fn timestamp(&self) -> SystemTime {
+        let now = self.now();
+        let mut last = self
+            .last_timestamp
+            .lock()
+            .expect("virtual clock lock poisoned");
+        let timestamp = match *last {
+            Some(last) if now <= last => last + Duration::from_micros(1),
+            _ => now,
+        };
+        *last = Some(timestamp);
+        timestamp
+    }
+
+    /// Lets virtual time run for the given duration, firing all timers due
+    /// in that time, and pauses it again.
+    pub async fn advance(&self, page: &Page, duration: Duration) -> Result<()> {
+        let mut expired = page
+            .event_listener::<emulation::EventVirtualTimeBudgetExpired>()
+            .await?;
+        page.execute(
+            emulation::SetVirtualTimePolicyParams::builder()
+                .policy(emulation::VirtualTimePolicy::Advance)
+                .budget(duration.as_secs_f64() * 1000.0)
+                .build()
+                .map_err(|err| {
+                    anyhow!(err)
+                        .context("build SetVirtualTimePolicyParams failed")
+                })?,
+        )
+        .await?;
+        let result =
+            tokio::time::timeout(ADVANCE_TIMEOUT, expired.next()).await;
+        *self.now.lock().expect("virtual clock lock poisoned") += duration;
+        if result.is_err() {
+            log::warn!(
+                "virtual time budget of {}ms didn't expire within {}s",
+                duration.as_millis(),
+                ADVANCE_TIMEOUT.as_secs()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamps_increase_while_the_clock_stands_still() {
+        let clock = VirtualClock {
+            now: Arc::new(Mutex::new(UNIX_EPOCH)),
+            last_timestamp: Arc::new(Mutex::new(None)),
+        };
+        let first = clock.timestamp();
+        let second = clock.timestamp();
+        assert_eq!(first, UNIX_EPOCH);
+        assert_eq!(second, UNIX_EPOCH + Duration::from_micros(1));
+
+        *clock.now.lock().unwrap() += Duration::from_millis(5);
+        assert_eq!(clock.timestamp(), UNIX_EPOCH + Duration::from_millis(5));
+    }
+}
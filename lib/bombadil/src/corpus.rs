@@ -0,0 +1,313 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde_json as json;
+use tokio::sync::Mutex;
+
+use crate::browser::actions::BrowserAction;
+use crate::browser::state::EdgeBucket;
+use crate::instrumentation::js::EDGE_MAP_SIZE;
+use crate::violations::fnv1a;
+
+const EDGES_FILE: &str = "edges.bin";
+const HASHES_FILE: &str = "hashes.json";
+const SEQUENCES_DIRECTORY: &str = "sequences";
+
+/// Number of saves after which the edge map and the hashes are written, as
+/// they're written whole every time.
+const SAVES_PER_FLUSH: usize = 16;
+
+/// A directory of action sequences that reached new coverage or new states,
+/// together with the coverage map and the states reached so far, so that a
+/// test can build on previous ones.
+///
+/// The directory contains:
+///
+/// * `edges.bin`: the global edge map, one bucket per byte
+/// * `hashes.json`: the transition hashes of all states reached
+/// * `sequences/*.json`: action sequences, each applied from the origin,
+///   none of which is a prefix of another
+pub struct Corpus {
+    directory: PathBuf,
+    edges: Option<Vec<EdgeBucket>>,
+    hashes: BTreeSet<u64>,
+    sequences: Vec<Vec<BrowserAction>>,
+    sequence_paths: Vec<PathBuf>,
+    unflushed: usize,
+}
+
+/// A corpus shared by several runners, e.g. parallel workers.
+pub type SharedCorpus = Arc<Mutex<Corpus>>;
+
+impl Corpus {
+    /// Opens a corpus directory, creating it if it doesn't exist.
+    pub async fn open(directory: PathBuf) -> Result<Self> {
+        let sequences_path = directory.join(SEQUENCES_DIRECTORY);
+        tokio::fs::create_dir_all(&sequences_path)
+            .await
+            .with_context(|| {
+                format!("failed to create corpus in {}", directory.display())
+            })?;
+
+        let edges = match tokio::fs::read(directory.join(EDGES_FILE)).await {
+            Ok(edges) if edges.len() == EDGE_MAP_SIZE => Some(edges),
+            Ok(edges) => {
+                log::warn!(
+                    "ignoring corpus edge map with {} edges (expected {})",
+                    edges.len(),
+                    EDGE_MAP_SIZE
+                );
+                None
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+
+        let hashes = match tokio::fs::read(directory.join(HASHES_FILE)).await {
+            Ok(content) => json::from_slice(&content)
+                .context("failed to parse corpus hashes")?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                BTreeSet::new()
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut sequence_paths = Vec::new();
+        let mut entries = tokio::fs::read_dir(&sequences_path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                sequence_paths.push(path);
+            }
+        }
+        // Sort for a deterministic order, so that seeds keep reproducing the
+        // choice of sequence.
+        sequence_paths.sort();
+        let mut sequences = Vec::with_capacity(sequence_paths.len());
+        for path in &sequence_paths {
+            sequences.push(read_sequence(path).await?);
+        }
+
+        log::info!(
+            "opened corpus in {} with {} sequences and {} states",
+            directory.display(),
+            sequences.len(),
+            hashes.len()
+        );
+
+        Ok(Corpus {
+            directory,
+            edges,
+            hashes,
+            sequences,
+            sequence_paths,
+            unflushed: 0,
+        })
+    }
+
+    pub fn shared(self) -> SharedCorpus {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// The edge map saved by previous tests, if any.
+    pub fn edges(&self) -> Option<&[EdgeBucket]> {
+        self.edges.as_deref()
+    }
+
+    pub fn sequences(&self) -> &[Vec<BrowserAction>] {
+        &self.sequences
+    }
+
+    /// Whether a state with the given transition hash has been reached
+    /// before, in this test or a previous one.
+    pub fn is_known_state(&self, hash: u64) -> bool {
+        self.hashes.contains(&hash)
+    }
+
+    /// Saves an action sequence that reached new coverage or a new state,
+    /// along with the current edge map and the state's transition hash.
+    /// Empty sequences only update the edge map and the hashes.
+    ///
+    /// Saved sequences that are prefixes of the new one are replaced by it,
+    /// as it goes through the same states. The edge map and the hashes are
+    /// only written every few saves, and by [`Corpus::flush`].
+    pub async fn save(
+        &mut self,
+        sequence: &[BrowserAction],
+        hash: Option<u64>,
+        edges: &[EdgeBucket],
+    ) -> Result<()> {
+        if !sequence.is_empty()
+            && !self
+                .sequences
+                .iter()
+                .any(|saved| saved.starts_with(sequence))
+        {
+            let api_sequence: Vec<bombadil_schema::BrowserAction> =
+                sequence.iter().map(BrowserAction::to_api).collect();
+            let content = json::to_vec(&api_sequence)?;
+            let path = self
+                .directory
+                .join(SEQUENCES_DIRECTORY)
+                .join(format!("{:016x}.json", fnv1a(&content)));
+            write_atomically(&path, &content).await?;
+            log::debug!("saved sequence to corpus: {}", path.display());
+
+            let mut index = 0;
+            while index < self.sequences.len() {
+                if sequence.starts_with(&self.sequences[index]) {
+                    let prefix_path = self.sequence_paths.remove(index);
+                    self.sequences.remove(index);
+                    tokio::fs::remove_file(&prefix_path).await?;
+                    log::debug!(
+                        "removed prefix sequence from corpus: {}",
+                        prefix_path.display()
+                    );
+                } else {
+                    index += 1;
+                }
+            }
+            self.sequences.push(sequence.to_vec());
+            self.sequence_paths.push(path);
+        }
+
+        if let Some(hash) = hash {
+            self.hashes.insert(hash);
+        }
+        self.edges = Some(edges.to_vec());
+        self.unflushed += 1;
+        if self.unflushed >= SAVES_PER_FLUSH {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the edge map and the hashes, if anything was saved since they
+    /// were last written.
+    pub async fn flush(&mut self) -> Result<()> {
+        if self.unflushed == 0 {
+            return Ok(());
+        }
+        write_atomically(
+            &self.directory.join(HASHES_FILE),
+            &json::to_vec(&self.hashes)?,
+        )
+        .await?;
+        if let Some(edges) = &self.edges {
+            write_atomically(&self.directory.join(EDGES_FILE), edges).await?;
+        }
+        self.unflushed = 0;
+        Ok(())
+    }
+}
+
+async fn read_sequence(path: &Path) -> Result<Vec<BrowserAction>> {
+    let content = tokio::fs::read(path).await?;
+    let sequence: Vec<bombadil_schema::BrowserAction> =
+        json::from_slice(&content).with_context(|| {
+            format!("failed to parse corpus sequence {}", path.display())
+        })?;
+    Ok(sequence.iter().map(BrowserAction::from_api).collect())
+}
+
+/// Writes to a temporary file first, so that a crash never leaves a
+/// half-written file behind.
+pub(crate) async fn write_atomically(
+    path: &Path,
+    content: &[u8],
+) -> Result<()> {
+    let temporary_path = path.with_extension("tmp");
+    tokio::fs::write(&temporary_path, content).await?;
+    tokio::fs::rename(&temporary_path, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_corpus_persists_across_opens() {
+        let directory = tempfile::TempDir::new().unwrap();
+        let mut corpus =
+            Corpus::open(directory.path().to_path_buf()).await.unwrap();
+        assert!(corpus.edges().is_none());
+        assert!(corpus.sequences().is_empty());
+
+        let mut edges = vec![0; EDGE_MAP_SIZE];
+        edges[42] = 3;
+        corpus.save(&[], Some(1), &edges).await.unwrap();
+        corpus
+            .save(
+                &[BrowserAction::Reload, BrowserAction::Back],
+                Some(2),
+                &edges,
+            )
+            .await
+            .unwrap();
+        assert!(corpus.is_known_state(1));
+        assert_eq!(corpus.sequences().len(), 1);
+        corpus.flush().await.unwrap();
+
+        let corpus =
+            Corpus::open(directory.path().to_path_buf()).await.unwrap();
+        assert_eq!(corpus.edges().unwrap()[42], 3);
+        assert!(corpus.is_known_state(1));
+        assert!(corpus.is_known_state(2));
+        assert!(!corpus.is_known_state(3));
+        assert_eq!(corpus.sequences().len(), 1);
+        assert!(matches!(
+            corpus.sequences()[0].as_slice(),
+            [BrowserAction::Reload, BrowserAction::Back]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_corpus_replaces_prefix_sequences() {
+        let directory = tempfile::TempDir::new().unwrap();
+        let mut corpus =
+            Corpus::open(directory.path().to_path_buf()).await.unwrap();
+        let edges = vec![0; EDGE_MAP_SIZE];
+        let history = [
+            BrowserAction::Reload,
+            BrowserAction::Back,
+            BrowserAction::Wait,
+        ];
+        for length in 1..=history.len() {
+            corpus.save(&history[..length], None, &edges).await.unwrap();
+        }
+        // Already covered by the longer sequence.
+        corpus.save(&history[..1], None, &edges).await.unwrap();
+        corpus
+            .save(&[BrowserAction::Forward], None, &edges)
+            .await
+            .unwrap();
+        assert_eq!(corpus.sequences().len(), 2);
+
+        let corpus =
+            Corpus::open(directory.path().to_path_buf()).await.unwrap();
+        assert_eq!(corpus.sequences().len(), 2);
+        assert!(
+            corpus
+                .sequences()
+                .iter()
+                .any(|sequence| sequence.len() == history.len())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corpus_ignores_mismatched_edge_map() {
+        let directory = tempfile::TempDir::new().unwrap();
+        tokio::fs::write(directory.path().join(EDGES_FILE), [1, 2, 3])
+            .await
+            .unwrap();
+        let corpus =
+            Corpus::open(directory.path().to_path_buf()).await.unwrap();
+        assert!(corpus.edges().is_none());
+    }
+}
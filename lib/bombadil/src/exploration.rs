@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::mem::{Discriminant, discriminant};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::browser::actions::BrowserAction;
+use crate::browser::state::{BrowserState, Coverage, EdgeBucket};
+use crate::instrumentation::js::EDGE_MAP_SIZE;
+use crate::tree::Tree;
+
+/// How much an action kind with a perfect discovery rate is favoured.
+const KIND_BOOST: f64 = 2.0;
+/// How much an action with a perfect discovery rate in a given state is
+/// favoured.
+const TRANSITION_BOOST: f64 = 6.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExplorationStrategy {
+    /// Pick actions using only the weights given by the specification.
+    Random,
+    /// Favour actions that have previously led to new coverage, both in
+    /// general (by action kind) and from the current state.
+    #[default]
+    CoverageGuided,
+}
+
+/// Identifies an action independently of incidental details, like the
+/// exact coordinates of a click or the text generated for typing.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ActionKey {
+    kind: Discriminant<BrowserAction>,
+    target: Option<String>,
+}
+
+impl ActionKey {
+    fn new(action: &BrowserAction) -> Self {
+        let target = match action {
+            BrowserAction::Click { name, content, .. }
+            | BrowserAction::DoubleClick { name, content, .. }
+            | BrowserAction::Tap { name, content, .. }
+            | BrowserAction::LongPress { name, content, .. }
+            | BrowserAction::Hover { name, content, .. }
+            | BrowserAction::ContextClick { name, content, .. } => Some(
+                format!("{}:{}", name, content.as_deref().unwrap_or_default()),
+            ),
+            BrowserAction::SetFileInputFiles { selector, .. }
+            | BrowserAction::SelectOptions { selector, .. }
+            | BrowserAction::SetInputValue { selector, .. } => {
+                Some(selector.clone())
+            }
+            BrowserAction::InjectFault { url_pattern, .. } => {
+                Some(url_pattern.clone())
+            }
+            BrowserAction::Resize { width, height } => {
+                Some(format!("{width}x{height}"))
+            }
+            _ => None,
+        };
+        ActionKey {
+            kind: discriminant(action),
+            target,
+        }
+    }
+}
+
+/// An action picked in a given state, to be credited with whatever the next
+/// observed state discovers.
+#[derive(Clone, Debug)]
+pub struct Picked {
+    transition_hash: Option<u64>,
+    key: ActionKey,
+}
+
+impl Picked {
+    pub fn new(state: &BrowserState, action: &BrowserAction) -> Self {
+        Picked {
+            transition_hash: state.transition_hash,
+            key: ActionKey::new(action),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Outcomes {
+    picked: u32,
+    discovered: u32,
+}
+
+impl Outcomes {
+    /// The rate of picks that discovered new coverage, smoothed so that
+    /// untried actions start out at 0.5.
+    fn discovery_rate(&self) -> f64 {
+        (self.discovered as f64 + 1.0) / (self.picked as f64 + 2.0)
+    }
+
+    fn record(&mut self, discovered: bool) {
+        self.picked = self.picked.saturating_add(1);
+        if discovered {
+            self.discovered = self.discovered.saturating_add(1);
+        }
+    }
+}
+
+/// Tracks global coverage and, depending on the strategy, which actions
+/// discover new edges.
+pub struct Exploration {
+    strategy: ExplorationStrategy,
+    edges: Box<[EdgeBucket]>,
+    kinds: HashMap<Discriminant<BrowserAction>, Outcomes>,
+    transitions: HashMap<(u64, ActionKey), Outcomes>,
+    last_discovery: Option<SystemTime>,
+}
+
+/// An exploration shared by several runners, e.g. parallel workers.
+pub type SharedExploration = Arc<Mutex<Exploration>>;
+
+impl Exploration {
+    pub fn new(strategy: ExplorationStrategy) -> Self {
+        Exploration {
+            strategy,
+            edges: vec![0; EDGE_MAP_SIZE].into_boxed_slice(),
+            kinds: HashMap::new(),
+            transitions: HashMap::new(),
+            last_discovery: None,
+        }
+    }
+
+    pub fn shared(strategy: ExplorationStrategy) -> SharedExploration {
+        Arc::new(Mutex::new(Exploration::new(strategy)))
+    }
+
+    /// The global edge map, holding the highest bucket seen per edge.
+    pub fn edges(&self) -> &[EdgeBucket] {
+        &self.edges
+    }
+
+    /// Merges the coverage of a new state into the global edge map, and
+    /// credits the action picked before it with whether it discovered
+    /// anything.
+    ///
+    /// Returns the number of edges that were hit for the first time, or in a
+    /// higher bucket than before.
+    pub fn observe(
+        &mut self,
+        state: &BrowserState,
+        picked: Option<Picked>,
+    ) -> usize {
+        let new_edges = self.merge(&state.coverage);
+        if new_edges > 0 {
+            self.last_discovery = Some(SystemTime::now());
+        }
+        if let Some(Picked {
+            transition_hash,
+            key,
+        }) = picked
+        {
+            let discovered = new_edges > 0;
+            self.kinds.entry(key.kind).or_default().record(discovered);
+            if let Some(hash) = transition_hash {
+                self.transitions
+                    .entry((hash, key))
+                    .or_default()
+                    .record(discovered);
+            }
+        }
+        new_edges
+    }
+
+    /// The wall-clock time at which a state last discovered new edges, if
+    /// any. Unlike the state's timestamp, it isn't virtual under virtual
+    /// time.
+    pub fn last_discovery(&self) -> Option<SystemTime> {
+        self.last_discovery
+    }
+
+    /// Merges an edge map saved by a previous test into the global one, so
+    /// that its edges no longer count as new.
+    pub fn restore_edges(&mut self, edges: &[EdgeBucket]) {
+        for (current, bucket) in self.edges.iter_mut().zip(edges) {
+            *current = (*current).max(*bucket);
+        }
+    }
+
+    /// Adjusts the weights of an action tree for the given state, according
+    /// to the strategy.
+    pub fn weigh(
+        &self,
+        state: &BrowserState,
+        actions: Tree<BrowserAction>,
+    ) -> Tree<BrowserAction> {
+        match self.strategy {
+            ExplorationStrategy::Random => actions,
+            ExplorationStrategy::CoverageGuided => {
+                actions.reweight(&|action| self.boost(state, action))
+            }
+        }
+    }
+
+    fn merge(&mut self, coverage: &Coverage) -> usize {
+        let mut new_edges = 0;
+        for (index, bucket) in &coverage.edges_new {
+            let current = &mut self.edges[*index as usize];
+            if *bucket > *current {
+                *current = *bucket;
+                new_edges += 1;
+            }
+        }
+        new_edges
+    }
+
+    fn boost(&self, state: &BrowserState, action: &BrowserAction) -> f64 {
+        let key = ActionKey::new(action);
+        let kind_rate = self
+            .kinds
+            .get(&key.kind)
+            .copied()
+            .unwrap_or_default()
+            .discovery_rate();
+        let transition_rate = state
+            .transition_hash
+            .and_then(|hash| self.transitions.get(&(hash, key)).copied())
+            .unwrap_or_default()
+            .discovery_rate();
+        (1.0 + KIND_BOOST * kind_rate)
+            * (1.0 + TRANSITION_BOOST * transition_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovery_rate() {
+        let mut outcomes = Outcomes::default();
+        assert_eq!(outcomes.discovery_rate(), 0.5);
+        outcomes.record(true);
+        assert!(outcomes.discovery_rate() > 0.5);
+        for _ in 0..10 {
+            outcomes.record(false);
+        }
+        assert!(outcomes.discovery_rate() < 0.5);
+    }
+
+    #[test]
+    fn test_action_key_ignores_coordinates() {
+        let click = |x: f64| BrowserAction::Click {
+            name: "BUTTON".to_string(),
+            content: Some("Save".to_string()),
+            point: crate::geometry::Point { x, y: 10.0 },
+        };
+        assert_eq!(ActionKey::new(&click(1.0)), ActionKey::new(&click(2.0)));
+        assert_ne!(
+            ActionKey::new(&click(1.0)),
+            ActionKey::new(&BrowserAction::Back)
+        );
+    }
+}
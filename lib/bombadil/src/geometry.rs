@@ -20,6 +20,13 @@ impl From<Point> for layout::Point {
 }
 
 impl Point {
+    pub fn from_api(point: &bombadil_schema::Point) -> Self {
+        Point {
+            x: point.x,
+            y: point.y,
+        }
+    }
+
     pub fn to_api(&self) -> bombadil_schema::Point {
         bombadil_schema::Point {
             x: self.x,
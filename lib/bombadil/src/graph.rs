@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use bombadil_browser_keys::chord_name;
+use bombadil_schema::{BrowserAction, TraceEntry};
+
+use crate::specification::convert::ToSchema;
+
+/// A model of the abstract states reached during a test, identified by their
+/// transition hashes, with the actions leading from one state to another as
+/// edges.
+#[derive(Debug, Clone, Default)]
+pub struct StateGraph {
+    nodes: BTreeMap<u64, Node>,
+    edges: BTreeMap<(u64, u64, String), u64>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    url: String,
+    visits: u64,
+}
+
+impl StateGraph {
+    pub fn new() -> Self {
+        StateGraph::default()
+    }
+
+    pub fn from_trace(entries: &[TraceEntry]) -> Self {
+        let mut graph = StateGraph::new();
+        for entry in entries {
+            graph.record(entry);
+        }
+        graph
+    }
+
+    /// Adds the state of a trace entry, and the transition leading to it.
+    /// Entries without a transition hash are ignored. Returns whether the
+    /// state wasn't in the graph yet.
+    pub fn record(&mut self, entry: &TraceEntry) -> bool {
+        let Some(current) = entry.hash_current else {
+            return false;
+        };
+        let node = self.nodes.entry(current).or_insert_with(|| Node {
+            url: entry.url.clone(),
+            visits: 0,
+        });
+        node.visits += 1;
+        let new = node.visits == 1;
+        if let (Some(previous), Some(action)) =
+            (entry.hash_previous, &entry.action)
+        {
+            *self
+                .edges
+                .entry((previous, current, action_label(action)))
+                .or_default() += 1;
+        }
+        new
+    }
+
+    fn exits(&self, hash: u64) -> u64 {
+        self.edges
+            .iter()
+            .filter(|((from, to, _), _)| *from == hash && *to != hash)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Renders the graph in Graphviz DOT format. States that were never left
+    /// are highlighted.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph states {\n    node [shape=box];\n");
+        for (hash, node) in &self.nodes {
+            let style = if self.exits(*hash) == 0 {
+                ", style=filled, fillcolor=\"#f4cccc\""
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{:016x}\" [label=\"{}\\n{:016x} ({} visits)\"{}];",
+                hash,
+                escape(&node.url),
+                hash,
+                node.visits,
+                style
+            );
+        }
+        for ((from, to, action), count) in &self.edges {
+            let _ = writeln!(
+                dot,
+                "    \"{:016x}\" -> \"{:016x}\" [label=\"{} ({})\"];",
+                from,
+                to,
+                escape(action),
+                count
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl ToSchema<bombadil_schema::StateGraph> for StateGraph {
+    fn to_schema(&self) -> bombadil_schema::StateGraph {
+        bombadil_schema::StateGraph {
+            states: self
+                .nodes
+                .iter()
+                .map(|(hash, node)| bombadil_schema::StateNode {
+                    hash: *hash,
+                    url: node.url.clone(),
+                    visits: node.visits,
+                    exits: self.exits(*hash),
+                })
+                .collect(),
+            transitions: self
+                .edges
+                .iter()
+                .map(|((from, to, action), count)| {
+                    bombadil_schema::StateTransition {
+                        from: *from,
+                        to: *to,
+                        action: action.clone(),
+                        count: *count,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Describes an action by its kind and target, leaving out incidental
+/// details like coordinates and typed text, so that equivalent actions form
+/// a single edge.
+fn action_label(action: &BrowserAction) -> String {
+    let target = |name: &str, content: &Option<String>| match content {
+        Some(content) => format!("<{name}> {content:?}"),
+        None => format!("<{name}>"),
+    };
+    match action {
+        BrowserAction::Back => "back".to_string(),
+        BrowserAction::Forward => "forward".to_string(),
+        BrowserAction::Reload => "reload".to_string(),
+        BrowserAction::Wait => "wait".to_string(),
+        BrowserAction::Click { name, content, .. } => {
+            format!("click {}", target(name, content))
+        }
+        BrowserAction::DoubleClick { name, content, .. } => {
+            format!("double-click {}", target(name, content))
+        }
+        BrowserAction::TypeText { .. } => "type text".to_string(),
+        BrowserAction::PressKey { code, modifiers } => {
+            match chord_name(*code, modifiers.into()) {
+                Some(chord) => format!("press key {chord}"),
+                None => format!("press key {code}"),
+            }
+        }
+        BrowserAction::ScrollUp { .. } => "scroll up".to_string(),
+        BrowserAction::ScrollDown { .. } => "scroll down".to_string(),
+        BrowserAction::SetFileInputFiles { selector, .. } => {
+            format!("set files {selector}")
+        }
+        BrowserAction::SelectOptions { selector, .. } => {
+            format!("select options {selector}")
+        }
+        BrowserAction::SetInputValue { selector, .. } => {
+            format!("set value {selector}")
+        }
+        BrowserAction::InjectFault { url_pattern, .. } => {
+            format!("inject fault {url_pattern}")
+        }
+        BrowserAction::GoOffline => "go offline".to_string(),
+        BrowserAction::GoOnline => "go online".to_string(),
+        BrowserAction::AdvanceClock { millis } => {
+            format!("advance clock {millis}ms")
+        }
+        BrowserAction::Resize { width, height } => {
+            format!("resize to {width}x{height}")
+        }
+        BrowserAction::SwitchTab { index } => format!("switch to tab {index}"),
+        BrowserAction::CloseTab { index } => format!("close tab {index}"),
+        BrowserAction::Tap { name, content, .. } => {
+            format!("tap {}", target(name, content))
+        }
+        BrowserAction::LongPress { name, content, .. } => {
+            format!("long-press {}", target(name, content))
+        }
+        BrowserAction::Swipe { .. } => "swipe".to_string(),
+        BrowserAction::Drag { .. } => "drag".to_string(),
+        BrowserAction::Hover { name, content, .. } => {
+            format!("hover {}", target(name, content))
+        }
+        BrowserAction::ContextClick { name, content, .. } => {
+            format!("context-click {}", target(name, content))
+        }
+        BrowserAction::Pinch { scale, .. } => {
+            if *scale >= 1.0 {
+                "pinch out".to_string()
+            } else {
+                "pinch in".to_string()
+            }
+        }
+        BrowserAction::RunSetupScript => "run setup script".to_string(),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        previous: Option<u64>,
+        current: Option<u64>,
+        action: Option<BrowserAction>,
+    ) -> TraceEntry {
+        TraceEntry {
+            timestamp: bombadil_schema::Time::from_system_time(
+                std::time::SystemTime::UNIX_EPOCH,
+            ),
+            episode: 0,
+            setup: false,
+            url: "http://localhost/".to_string(),
+            hash_previous: previous,
+            hash_current: current,
+            action,
+            screenshot: String::new(),
+            snapshots: vec![],
+            violations: vec![],
+            network: vec![],
+            storage: Default::default(),
+            resources: bombadil_schema::Resources {
+                js_heap_used: 0,
+                js_heap_total: 0,
+                dom_nodes: 0,
+                documents: 0,
+                js_event_listeners: 0,
+                layout_objects: 0,
+                timestamp: 0.0,
+                thread_time: 0.0,
+                task_duration: 0.0,
+                script_duration: 0.0,
+            },
+        }
+    }
+
+    fn click(content: &str, x: f64) -> BrowserAction {
+        BrowserAction::Click {
+            name: "BUTTON".to_string(),
+            content: Some(content.to_string()),
+            point: bombadil_schema::Point { x, y: 0.0 },
+        }
+    }
+
+    #[test]
+    fn test_graph_from_trace() {
+        let graph = StateGraph::from_trace(&[
+            entry(None, Some(1), None),
+            entry(Some(1), Some(2), Some(click("Next", 10.0))),
+            entry(Some(2), Some(1), Some(BrowserAction::Back)),
+            entry(Some(1), Some(2), Some(click("Next", 20.0))),
+            entry(Some(2), Some(3), Some(click("Done", 10.0))),
+            entry(Some(3), Some(3), Some(BrowserAction::Wait)),
+        ])
+        .to_schema();
+
+        let visits: Vec<(u64, u64, u64)> = graph
+            .states
+            .iter()
+            .map(|state| (state.hash, state.visits, state.exits))
+            .collect();
+        assert_eq!(visits, vec![(1, 2, 2), (2, 2, 2), (3, 2, 0)]);
+
+        let next = graph
+            .transitions
+            .iter()
+            .find(|transition| transition.from == 1 && transition.to == 2)
+            .unwrap();
+        assert_eq!(next.action, "click <BUTTON> \"Next\"");
+        assert_eq!(next.count, 2);
+        assert_eq!(graph.transitions.len(), 4);
+    }
+
+    #[test]
+    fn test_record_reports_new_states() {
+        let mut graph = StateGraph::new();
+        assert!(graph.record(&entry(None, Some(1), None)));
+        assert!(graph.record(&entry(Some(1), Some(2), Some(click("a", 0.0)))));
+        assert!(!graph.record(&entry(
+            Some(2),
+            Some(1),
+            Some(BrowserAction::Back)
+        )));
+        assert!(!graph.record(&entry(None, None, None)));
+    }
+
+    #[test]
+    fn test_graph_ignores_entries_without_hash() {
+        let graph = StateGraph::from_trace(&[
+            entry(None, None, None),
+            entry(None, Some(1), Some(BrowserAction::Reload)),
+        ])
+        .to_schema();
+        assert_eq!(graph.states.len(), 1);
+        assert!(graph.transitions.is_empty());
+    }
+
+    #[test]
+    fn test_dot_highlights_dead_ends() {
+        let dot = StateGraph::from_trace(&[
+            entry(None, Some(1), None),
+            entry(Some(1), Some(2), Some(click("Say \"hi\"", 0.0))),
+        ])
+        .to_dot();
+        assert!(dot.starts_with("digraph states {"));
+        assert!(dot.contains(
+            "\"0000000000000001\" -> \"0000000000000002\" [label=\"click <BUTTON> \\\"Say \\\\\\\"hi\\\\\\\"\\\" (1)\"];"
+        ));
+        let dead_ends: Vec<&str> = dot
+            .lines()
+            .filter(|line| line.contains("fillcolor"))
+            .collect();
+        assert_eq!(dead_ends.len(), 1);
+        assert!(dead_ends[0].starts_with("    \"0000000000000002\""));
+    }
+}
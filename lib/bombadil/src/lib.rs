@@ -1,9 +1,14 @@
 pub mod browser;
+pub mod corpus;
+pub mod exploration;
 pub mod geometry;
+pub mod graph;
 pub mod instrumentation;
 pub mod runner;
+pub mod shrink;
 pub mod specification;
 pub mod styled;
 pub mod trace;
 pub mod tree;
 pub mod url;
+pub mod violations;
@@ -1,23 +1,31 @@
 use crate::browser::actions::BrowserAction;
 use crate::browser::{BrowserEvent, BrowserOptions};
-use crate::instrumentation::js::EDGE_MAP_SIZE;
+use crate::corpus::{Corpus, SharedCorpus};
+use crate::exploration::{
+    Exploration, ExplorationStrategy, Picked, SharedExploration,
+};
 use crate::specification::bundler::bundle;
 use crate::specification::convert::ToSchema;
 use crate::specification::domain::Snapshot;
-use crate::specification::verifier::Specification;
+use crate::specification::js::JsAction;
+use crate::specification::verifier::{Setup, Specification};
 use crate::specification::worker::{PropertyValue, VerifierWorker};
 use crate::trace::PropertyViolation;
 use ::url::Url;
 use bombadil_schema::Time;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde::Deserialize;
 use serde_json as json;
-use std::cmp::max;
+use std::collections::VecDeque;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 use tokio::select;
 use tokio::signal::ctrl_c;
+use tokio::sync::Mutex;
 
-use crate::browser::state::{BrowserState, Coverage};
+use crate::browser::state::{BrowserState, Coverage, EdgeBucket};
 use crate::browser::{Browser, DebuggerOptions};
 use crate::url::is_within_domain;
 
@@ -50,22 +58,89 @@ pub trait RunObserver {
     fn on_interrupted(
         &mut self,
     ) -> impl std::future::Future<Output = anyhow::Result<Self::StopValue>>;
+
+    /// Called when the browser is reset to start a new episode, before the
+    /// first state of that episode.
+    fn on_new_episode(
+        &mut self,
+        _episode: u64,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> {
+        async { Ok(()) }
+    }
+
+    /// Called before the first step of the specification's setup is applied,
+    /// at the start of the test and of every episode.
+    fn on_setup_started(
+        &mut self,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> {
+        async { Ok(()) }
+    }
+
+    /// Called once all steps of the specification's setup have been applied,
+    /// before the first other action.
+    fn on_setup_finished(
+        &mut self,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> {
+        async { Ok(()) }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RunnerOptions {
+    /// Seeds every random choice made during the test: picking actions and
+    /// the generators used by the specification.
+    pub seed: u64,
+    pub exploration: ExplorationStrategy,
+    pub episodes: EpisodeOptions,
+}
+
+/// Splits a test into episodes, each starting from the origin with cleared
+/// cookies and storage, and with a fresh evaluation of the specification.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EpisodeOptions {
+    /// Number of actions after which an episode ends.
+    pub length: Option<u64>,
+    /// Number of episodes after which the test ends.
+    pub count: Option<u64>,
+}
+
+impl EpisodeOptions {
+    fn is_enabled(&self) -> bool {
+        self.length.is_some() || self.count.is_some()
+    }
+}
+
+/// Where the runner gets its actions from.
+enum RunMode {
+    /// Apply the given prefix of actions in order (e.g. a sequence from the
+    /// corpus), then pick actions from the specification's action generators.
+    Explore { prefix: VecDeque<BrowserAction> },
+    /// Apply the given actions in order, stopping when they run out.
+    Replay(VecDeque<BrowserAction>),
 }
 
 pub struct Runner {
     origin: Url,
     browser: Browser,
+    specification: Specification,
     verifier: Arc<VerifierWorker>,
+    rng: ChaCha8Rng,
+    exploration: SharedExploration,
+    corpus: Option<SharedCorpus>,
+    episodes: EpisodeOptions,
 }
 
 impl Runner {
     pub async fn new(
         origin: Url,
         specification: Specification,
+        runner_options: RunnerOptions,
         browser_options: BrowserOptions,
         debugger_options: DebuggerOptions,
     ) -> anyhow::Result<Self> {
-        let verifier = VerifierWorker::start(specification.clone()).await?;
+        let mut rng = ChaCha8Rng::seed_from_u64(runner_options.seed);
+        let verifier =
+            VerifierWorker::start(specification.clone(), rng.random()).await?;
 
         let browser =
             Browser::new(origin.clone(), browser_options, debugger_options)
@@ -80,48 +155,134 @@ impl Runner {
         Ok(Runner {
             origin,
             browser,
+            specification,
             verifier,
+            rng,
+            exploration: Exploration::shared(runner_options.exploration),
+            corpus: None,
+            episodes: runner_options.episodes,
         })
     }
 
+    /// Shares the coverage map and the record of which actions discover new
+    /// coverage with other runners, instead of exploring on its own.
+    pub fn with_exploration(mut self, exploration: SharedExploration) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Saves action sequences that reach new coverage or new states to the
+    /// corpus, with the coverage of previous tests restored, and starts
+    /// exploring each episode from the end of a saved sequence.
+    pub fn with_corpus(mut self, corpus: SharedCorpus) -> Self {
+        self.corpus = Some(corpus);
+        self
+    }
+
     pub async fn run<O: RunObserver>(
         mut self,
         observer: &mut O,
     ) -> anyhow::Result<Option<O::StopValue>> {
         log::info!("starting test of {}", self.origin);
+        if let Some(corpus) = &self.corpus
+            && let Some(edges) = corpus.lock().await.edges()
+        {
+            self.exploration
+                .lock()
+                .expect("exploration lock poisoned")
+                .restore_edges(edges);
+        }
+        let prefix = corpus_prefix(self.corpus.as_deref(), &mut self.rng).await;
+        self.run_with_mode(RunMode::Explore { prefix }, observer)
+            .await
+    }
 
+    /// Applies previously recorded actions in order, instead of picking new
+    /// ones, while still checking the specification's properties. Returns
+    /// `None` when all actions have been applied.
+    pub async fn replay<O: RunObserver>(
+        self,
+        actions: Vec<BrowserAction>,
+        observer: &mut O,
+    ) -> anyhow::Result<Option<O::StopValue>> {
+        log::info!(
+            "replaying {} actions against {}",
+            actions.len(),
+            self.origin
+        );
+        self.run_with_mode(RunMode::Replay(actions.into()), observer)
+            .await
+    }
+
+    async fn run_with_mode<O: RunObserver>(
+        mut self,
+        mut mode: RunMode,
+        observer: &mut O,
+    ) -> anyhow::Result<Option<O::StopValue>> {
         self.browser.initiate().await?;
         log::debug!("browser initiated");
 
-        let result = Runner::run_test(
-            &self.origin,
-            &mut self.browser,
-            self.verifier,
-            observer,
-        )
-        .await;
+        let result = self.run_test(&mut mode, observer).await;
 
         log::debug!("test finished");
 
+        // Also when the test failed, to keep what it found so far.
+        let flushed = match &self.corpus {
+            Some(corpus) => corpus.lock().await.flush().await,
+            None => Ok(()),
+        };
+
         self.browser
             .terminate()
             .await
             .expect("browser failed to terminate");
 
-        result
+        let result = result?;
+        flushed?;
+        Ok(result)
     }
 
     async fn run_test<O: RunObserver>(
-        origin: &Url,
-        browser: &mut Browser,
-        verifier: Arc<VerifierWorker>,
+        &mut self,
+        mode: &mut RunMode,
         observer: &mut O,
     ) -> anyhow::Result<Option<O::StopValue>> {
+        let Runner {
+            origin,
+            browser,
+            specification,
+            verifier,
+            rng,
+            exploration,
+            corpus,
+            episodes,
+        } = self;
+        let corpus = corpus.as_deref();
+        // Episodes only apply when exploring, as replayed actions are meant
+        // to be applied in a single sequence.
+        let episodic =
+            matches!(mode, RunMode::Explore { .. }) && episodes.is_enabled();
+
         let mut last_action: Option<BrowserAction> = None;
-        let mut edges = [0u8; EDGE_MAP_SIZE];
+        let mut last_picked: Option<Picked> = None;
+        // All actions applied since the origin was loaded, to be saved in the
+        // corpus.
+        let mut history: Vec<BrowserAction> = Vec::new();
+        let mut episode: u64 = 0;
+        let mut episode_actions: u64 = 0;
+        // The specification's setup is applied from the origin before any
+        // other action, in every episode. Its steps aren't saved in the
+        // corpus, as they're applied before the corpus sequence anyway.
+        let setup_actions = setup_actions(verifier).await?;
+        let mut setup: VecDeque<BrowserAction> =
+            setup_actions.iter().cloned().collect();
+        let mut in_setup = false;
+        // The setup step waiting for a state that follows it, with how many
+        // times it's been sent (as the browser drops actions sent while a
+        // page is loading), and how many applied actions that state has.
+        let mut setup_pending: Option<(BrowserAction, u32, u64)> = None;
 
         loop {
-            let verifier = verifier.clone();
             select! {
                 event = browser.next_event() => {
                     match event {
@@ -177,13 +338,40 @@ impl Runner {
                                         action_tree
                                     };
 
-                                // Update global edges.
-                                for (index, bucket) in &state.coverage.edges_new {
-                                    edges[*index as usize] =
-                                        max(edges[*index as usize], *bucket);
+                                // Update global edges, and save the actions
+                                // leading here if they found anything new.
+                                {
+                                    let mut corpus = match corpus {
+                                        Some(corpus) => Some(corpus.lock().await),
+                                        None => None,
+                                    };
+                                    let new_state = state.transition_hash.is_some_and(|hash| {
+                                        corpus
+                                            .as_ref()
+                                            .is_some_and(|corpus| !corpus.is_known_state(hash))
+                                    });
+                                    let edges_to_save = {
+                                        let mut exploration = exploration
+                                            .lock()
+                                            .expect("exploration lock poisoned");
+                                        let new_edges = exploration
+                                            .observe(&state, last_picked.take());
+                                        log::debug!("new edges: {}", new_edges);
+                                        log_coverage_stats_increment(&state.coverage);
+                                        log_coverage_stats_total(exploration.edges());
+                                        (corpus.is_some()
+                                            && !in_setup
+                                            && (new_edges > 0 || new_state))
+                                            .then(|| exploration.edges().to_vec())
+                                    };
+                                    if let (Some(corpus), Some(edges)) =
+                                        (corpus.as_mut(), edges_to_save)
+                                    {
+                                        corpus
+                                            .save(&history, state.transition_hash, &edges)
+                                            .await?;
+                                    }
                                 }
-                                log_coverage_stats_increment(&state.coverage);
-                                log_coverage_stats_total(&edges);
 
                                 let control = observer
                                     .on_new_state(
@@ -198,23 +386,132 @@ impl Runner {
                                     return Ok(Some(value));
                                 }
 
+                                let episode_finished = episodic
+                                    && (!step_result.has_pending
+                                        || episodes
+                                            .length
+                                            .is_some_and(|length| episode_actions >= length));
+                                if episode_finished {
+                                    if episodes.count.is_some_and(|count| episode + 1 >= count) {
+                                        log::info!("all episodes finished, stopping");
+                                        return Ok(None);
+                                    }
+                                    episode += 1;
+                                    log::info!("starting episode {}", episode);
+                                    episode_actions = 0;
+                                    last_action = None;
+                                    history.clear();
+                                    *verifier = VerifierWorker::start(
+                                        specification.clone(),
+                                        rng.random(),
+                                    )
+                                    .await?;
+                                    setup = setup_actions.iter().cloned().collect();
+                                    in_setup = false;
+                                    setup_pending = None;
+                                    if let RunMode::Explore { prefix } = mode {
+                                        *prefix = corpus_prefix(corpus, rng).await;
+                                    }
+                                    observer.on_new_episode(episode).await?;
+                                    browser.reset()?;
+                                    continue;
+                                }
+
                                 if !step_result.has_pending {
                                     log::info!("all properties are definite, stopping");
                                     return Ok(None);
                                 }
 
-                                let action_tree =
-                                    action_tree.prune().ok_or_else(|| {
-                                        anyhow::anyhow!("no actions available")
-                                    })?;
+                                // A setup step is only followed by the next
+                                // one from a state captured after the browser
+                                // applied it. Steps the browser dropped, as
+                                // they arrived while a page was loading, are
+                                // sent again from the state of the loaded page.
+                                let applied = state.applied_actions + 1;
+                                let setup_step = match setup_pending.take() {
+                                    Some((step, attempts, expected))
+                                        if state.applied_actions < expected =>
+                                    {
+                                        if attempts >= MAX_SETUP_STEP_ATTEMPTS {
+                                            anyhow::bail!(
+                                                "setup step {:?} wasn't applied after {} attempts",
+                                                step,
+                                                attempts
+                                            );
+                                        }
+                                        log::debug!(
+                                            "state doesn't follow setup step {:?}, sending it again",
+                                            step
+                                        );
+                                        setup_pending =
+                                            Some((step.clone(), attempts + 1, applied));
+                                        Some(step)
+                                    }
+                                    _ => setup.pop_front().inspect(|step| {
+                                        setup_pending = Some((step.clone(), 1, applied));
+                                    }),
+                                };
+                                if setup_step.is_some() && !in_setup {
+                                    observer.on_setup_started().await?;
+                                } else if setup_step.is_none() && in_setup {
+                                    observer.on_setup_finished().await?;
+                                }
+                                in_setup = setup_step.is_some();
 
-                                let action =
-                                    action_tree.pick(&mut rand::rng())?.clone();
+                                let action = match (setup_step, mode) {
+                                    (Some(action), _) => action,
+                                    (None, RunMode::Explore { prefix }) if !prefix.is_empty() => {
+                                        prefix.pop_front().expect("prefix is not empty")
+                                    }
+                                    (None, RunMode::Explore { .. }) => {
+                                        let action_tree =
+                                            action_tree.prune().ok_or_else(|| {
+                                                anyhow::anyhow!("no actions available")
+                                            })?;
+
+                                        let action_tree = exploration
+                                            .lock()
+                                            .expect("exploration lock poisoned")
+                                            .weigh(&state, action_tree);
+                                        let action =
+                                            action_tree.pick(rng)?.clone();
+                                        last_picked =
+                                            Some(Picked::new(&state, &action));
+                                        action
+                                    }
+                                    (None, RunMode::Replay(actions)) => {
+                                        match actions.pop_front() {
+                                            Some(action) => action,
+                                            None => {
+                                                log::info!("all actions replayed, stopping");
+                                                return Ok(None);
+                                            }
+                                        }
+                                    }
+                                };
                                 let timeout = action_timeout(&action);
                                 log::info!("picked action: {:?}", action);
                                 browser.apply(action.clone(), timeout)?;
+                                if !in_setup {
+                                    if corpus.is_some() {
+                                        history.push(action.clone());
+                                    }
+                                    episode_actions += 1;
+                                }
                                 last_action = Some(action);
                             }
+                            BrowserEvent::ActionFailed(action, error) => {
+                                // Exploration carries on past failing
+                                // actions, but without its setup the test
+                                // wouldn't explore what it's meant to.
+                                if in_setup {
+                                    anyhow::bail!(
+                                        "setup step {:?} failed: {}",
+                                        action,
+                                        error
+                                    );
+                                }
+                            }
                             BrowserEvent::Error(error) => {
                                 anyhow::bail!("state machine error: {}", error)
                             }
@@ -233,6 +530,43 @@ impl Runner {
     }
 }
 
+/// Picks a sequence from the corpus to start exploring from, if there's one.
+async fn corpus_prefix(
+    corpus: Option<&Mutex<Corpus>>,
+    rng: &mut ChaCha8Rng,
+) -> VecDeque<BrowserAction> {
+    let Some(corpus) = corpus else {
+        return VecDeque::new();
+    };
+    match corpus.lock().await.sequences().choose(rng) {
+        Some(sequence) => {
+            log::info!(
+                "starting from a corpus sequence of {} actions",
+                sequence.len()
+            );
+            sequence.iter().cloned().collect()
+        }
+        None => VecDeque::new(),
+    }
+}
+
+/// How many times a setup step is sent before giving up on it, when the
+/// states that follow were captured before the browser applied it.
+const MAX_SETUP_STEP_ATTEMPTS: u32 = 3;
+
+async fn setup_actions(
+    verifier: &VerifierWorker,
+) -> anyhow::Result<Vec<BrowserAction>> {
+    Ok(match verifier.setup::<JsAction>().await? {
+        Some(Setup::Actions(actions)) => actions
+            .into_iter()
+            .map(JsAction::to_browser_action)
+            .collect::<anyhow::Result<_>>()?,
+        Some(Setup::Script) => vec![BrowserAction::RunSetupScript],
+        None => vec![],
+    })
+}
+
 async fn run_extractors(
     state: &BrowserState,
     last_action: &Option<BrowserAction>,
@@ -249,12 +583,39 @@ async fn run_extractors(
         })
         .collect();
 
+    let network_requests: Vec<json::Value> = state
+        .network_requests
+        .iter()
+        .map(|request| {
+            json::json!({
+                "timestamp": request
+                    .timestamp
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64()
+                    * 1000.0,
+                "url": request.url,
+                "method": request.method,
+                "resourceType": request.resource_type,
+                "status": request.status,
+                "durationMillis": request.duration_millis,
+                "failure": request.failure,
+            })
+        })
+        .collect();
+
     let state_partial = json::json!({
         "errors": {
             "uncaughtExceptions": &state.exceptions,
         },
         "console": console_entries,
+        "network": network_requests,
         "navigationHistory": &state.navigation_history,
+        "tabs": &state.tabs,
+        "connectivity": &state.connectivity,
+        "touch": state.touch,
+        "storage": &state.storage,
+        "accessibilityTree": &state.accessibility_tree,
         "lastAction": json::to_value(last_action)?,
     });
 
@@ -300,7 +661,7 @@ async fn run_extractors(
     Ok(results)
 }
 
-fn action_timeout(action: &BrowserAction) -> Duration {
+pub fn action_timeout(action: &BrowserAction) -> Duration {
     match action {
         BrowserAction::Back => Duration::from_secs(2),
         BrowserAction::Forward => Duration::from_secs(2),
@@ -322,6 +683,33 @@ fn action_timeout(action: &BrowserAction) -> Duration {
         BrowserAction::ScrollDown { .. } => Duration::from_millis(100),
         BrowserAction::Wait => Duration::from_secs(1),
         BrowserAction::SetFileInputFiles { .. } => Duration::from_millis(100),
+        BrowserAction::SelectOptions { .. } => Duration::from_millis(100),
+        BrowserAction::SetInputValue { .. } => Duration::from_millis(100),
+        BrowserAction::InjectFault { .. } => Duration::from_millis(100),
+        BrowserAction::GoOffline => Duration::from_millis(500),
+        BrowserAction::GoOnline => Duration::from_millis(500),
+        BrowserAction::AdvanceClock { millis } => {
+            Duration::from_millis(*millis)
+        }
+        BrowserAction::Resize { .. } => Duration::from_millis(500),
+        BrowserAction::SwitchTab { .. } => Duration::from_millis(500),
+        BrowserAction::CloseTab { .. } => Duration::from_millis(500),
+        BrowserAction::Tap { .. } => Duration::from_millis(500),
+        BrowserAction::LongPress {
+            duration_millis, ..
+        } => Duration::from_millis(duration_millis.saturating_add(500)),
+        BrowserAction::Swipe { .. } => Duration::from_millis(500),
+        BrowserAction::Pinch { .. } => Duration::from_millis(500),
+        BrowserAction::Hover { .. } => Duration::from_millis(500),
+        BrowserAction::ContextClick { .. } => Duration::from_millis(500),
+        BrowserAction::Drag { steps, .. } => {
+            // Each step is a mouse move with a short delay, and possibly a
+            // drag event.
+            Duration::from_millis((*steps as u64).saturating_mul(100) + 500)
+        }
+        // Setup scripts usually fill in and submit forms, so they get
+        // enough time for a page load.
+        BrowserAction::RunSetupScript => Duration::from_secs(5),
     }
 }
 
@@ -341,7 +729,7 @@ fn log_coverage_stats_increment(coverage: &Coverage) {
     }
 }
 
-fn log_coverage_stats_total(edges: &[u8; EDGE_MAP_SIZE]) {
+fn log_coverage_stats_total(edges: &[EdgeBucket]) {
     if log::log_enabled!(log::Level::Debug) {
         let mut buckets = [0u64; 8];
         let mut hits_total: u64 = 0;
@@ -0,0 +1,103 @@
+use std::future::Future;
+
+/// Minimizes a sequence while it keeps satisfying `still_fails`, using the
+/// delta debugging algorithm: first try removing large chunks, and then
+/// gradually smaller ones, down to single elements.
+///
+/// The result is 1-minimal: removing any single element from it makes
+/// `still_fails` return false. The original sequence is assumed to fail.
+pub async fn shrink<T, F, Fut>(
+    items: Vec<T>,
+    mut still_fails: F,
+) -> anyhow::Result<Vec<T>>
+where
+    T: Clone,
+    F: FnMut(Vec<T>) -> Fut,
+    Fut: Future<Output = anyhow::Result<bool>>,
+{
+    let mut current = items;
+    let mut granularity = 2;
+
+    while !current.is_empty() {
+        granularity = granularity.min(current.len());
+        let chunk_size = current.len().div_ceil(granularity);
+
+        let mut reduced = false;
+        for start in (0..current.len()).step_by(chunk_size) {
+            let end = (start + chunk_size).min(current.len());
+            let candidate: Vec<T> = current[..start]
+                .iter()
+                .chain(&current[end..])
+                .cloned()
+                .collect();
+            log::debug!(
+                "trying to remove {} of {} elements",
+                end - start,
+                current.len()
+            );
+            if still_fails(candidate.clone()).await? {
+                log::info!("shrunk to {} elements", candidate.len());
+                current = candidate;
+                granularity = (granularity - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced {
+            if granularity >= current.len() {
+                break;
+            }
+            granularity = (granularity * 2).min(current.len());
+        }
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shrink_keeps_required_elements() {
+        let items: Vec<u32> = (0..20).collect();
+        let result = shrink(items, |candidate| async move {
+            Ok(candidate.contains(&3) && candidate.contains(&17))
+        })
+        .await
+        .unwrap();
+        assert_eq!(result, vec![3, 17]);
+    }
+
+    #[tokio::test]
+    async fn test_shrink_preserves_order() {
+        let items = vec![5, 1, 4, 2, 3];
+        let result = shrink(items, |candidate| async move {
+            let position = |x| candidate.iter().position(|y| *y == x);
+            Ok(matches!(
+                (position(4), position(3)),
+                (Some(a), Some(b)) if a < b
+            ))
+        })
+        .await
+        .unwrap();
+        assert_eq!(result, vec![4, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_shrink_to_empty() {
+        let result =
+            shrink(vec![1, 2, 3], |_| async { Ok(true) }).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shrink_propagates_errors() {
+        let result = shrink(vec![1, 2, 3], |_| async {
+            Err::<bool, _>(anyhow::anyhow!("browser closed"))
+        })
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -7,6 +7,7 @@ export {
   emails,
   integers,
   keycodes,
+  matching,
   randomRange,
 } from "@antithesishq/bombadil/random";
 
@@ -30,10 +31,45 @@ export type Action =
       };
     }
   | { TypeText: { text: string; delayMillis: number } }
-  | { PressKey: { code: number } }
+  | { PressKey: { code: number; modifiers?: KeyModifiers } }
   | { ScrollUp: { origin: Point; distance: number } }
   | { ScrollDown: { origin: Point; distance: number } }
-  | { SetFileInputFiles: { selector: string; files: string[] } };
+  | { SetFileInputFiles: { selector: string; files: string[] } }
+  | { SelectOptions: { selector: string; values: string[] } }
+  | { SetInputValue: { selector: string; value: string } }
+  | { InjectFault: { urlPattern: string; fault: NetworkFault } }
+  | "GoOffline"
+  | "GoOnline"
+  | { AdvanceClock: { millis: number } }
+  | { Resize: { width: number; height: number } }
+  | { SwitchTab: { index: number } }
+  | { CloseTab: { index: number } }
+  | { Tap: { name: string; content?: string; point: Point } }
+  | {
+      LongPress: {
+        name: string;
+        content?: string;
+        point: Point;
+        durationMillis: number;
+      };
+    }
+  | { Swipe: { start: Point; end: Point } }
+  | { Pinch: { origin: Point; scale: number } }
+  | { Drag: { from: Point; to: Point; steps: number } }
+  | { Hover: { name: string; content?: string; point: Point } }
+  | { ContextClick: { name: string; content?: string; point: Point } };
+
+export type KeyModifiers = {
+  ctrl?: boolean;
+  alt?: boolean;
+  shift?: boolean;
+  meta?: boolean;
+};
+
+export type NetworkFault =
+  | "Fail"
+  | { Status: { code: number } }
+  | { Delay: { millis: number } };
 
 // Tree
 
@@ -8,16 +8,38 @@ export {
 import {
   scroll,
   clicks,
+  hovers,
+  contextClicks,
+  drags,
+  longPresses,
+  pinches,
   inputs,
+  selects,
+  toggles,
+  inputValues,
+  shortcuts,
   navigation,
+  resizes,
+  tabs,
   waitOnce,
 } from "@antithesishq/bombadil/defaults/actions";
 import { weighted } from "@antithesishq/bombadil/actions";
 
 export const defaultActions = weighted([
   [10, clicks],
+  [3, hovers],
+  [1, contextClicks],
   [10, inputs],
+  [3, selects],
+  [3, toggles],
+  [3, inputValues],
+  [1, shortcuts],
   [5, scroll],
+  [2, drags],
+  [2, longPresses],
+  [1, pinches],
   [1, navigation],
+  [1, resizes],
+  [1, tabs],
   [1, waitOnce],
 ]);
@@ -6,7 +6,10 @@ import {
   emails,
   integers,
   keycodes,
+  matching,
+  from,
   type Action,
+  type ActionGenerator,
   Cell,
 } from "@antithesishq/bombadil";
 
@@ -26,6 +29,10 @@ const canGoForwardSameOrigin = extract((state) => {
   }
 });
 
+// Touch emulation is on when running with `--mobile`, in which case the
+// generators below tap and swipe instead of clicking and scrolling.
+const touchEnabled = extract((state) => state.touch);
+
 export const lastAction: Cell<Action | null> = extract(
   (state) => state.lastAction,
 );
@@ -65,6 +72,27 @@ export const scroll = actions(() => {
   const scrollYMax = body.current.scrollHeight - window.current.inner.height;
   const scrollYMaxDiff = scrollYMax - window.current.scroll.y;
 
+  if (touchEnabled.current) {
+    const center = {
+      x: window.current.inner.width / 2,
+      y: window.current.inner.height / 2,
+    };
+    // Swiping up scrolls down, and swiping down scrolls up.
+    const distance =
+      scrollYMaxDiff >= 1
+        ? Math.min(window.current.inner.height / 2, scrollYMaxDiff)
+        : -Math.min(window.current.inner.height / 2, window.current.scroll.y);
+    if (distance === 0) return [];
+    return [
+      {
+        Swipe: {
+          start: { x: center.x, y: center.y + distance / 2 },
+          end: { x: center.x, y: center.y - distance / 2 },
+        },
+      } as Action,
+    ];
+  }
+
   if (scrollYMaxDiff >= 1) {
     return [
       {
@@ -189,7 +217,6 @@ const clickablePoints = extract((state) => {
       continue;
     }
 
-    if (anchor.target === "_blank") continue;
     if (!url.protocol.startsWith("http")) continue;
     if (url.hostname !== urlCurrent.hostname) continue;
     if (url.port !== "" && url.port !== urlCurrent.port) continue;
@@ -207,10 +234,10 @@ const clickablePoints = extract((state) => {
     added.add(anchor);
   }
 
-  // Buttons, inputs, textareas, labels
+  // Buttons, inputs, textareas, labels, editable regions
   for (const element of queryAll(
     state.document.body,
-    "button,input,textarea,label[for]",
+    'button,input,textarea,label[for],[contenteditable]:not([contenteditable="false"])',
   )) {
     if (added.has(element)) continue;
     // We require visibility except for input elements, which are often hidden and overlayed with custom styling.
@@ -266,14 +293,255 @@ const clickablePoints = extract((state) => {
 
 export const clicks = actions(() => {
   if (contentType.current !== "text/html") return [];
+  return clickablePoints.current.map(({ name, content, point }) =>
+    touchEnabled.current
+      ? ({ Tap: { name, content, point } } as Action)
+      : ({ Click: { name, content, point } } as Action),
+  );
+});
+
+// Hovers and context clicks
+
+const pointerTargets = extract((state) => {
+  type Target = {
+    name: string;
+    content: string;
+    point: { x: number; y: number };
+  };
+  // Populated by Bombadil in every page, see `EVENT_LISTENERS_SCRIPT`.
+  const listeners = (
+    state.window as unknown as {
+      __bombadil_listeners__?: { elements: (type: string) => Element[] };
+    }
+  ).__bombadil_listeners__;
+
+  function target(element: Element): Target | null {
+    const style = state.window.getComputedStyle(element);
+    if (style.display === "none" || style.visibility === "hidden") return null;
+    const rect = element.getBoundingClientRect();
+    if (rect.width <= 0 || rect.height <= 0) return null;
+    const point = {
+      x: rect.left + rect.width / 2,
+      y: rect.top + rect.height / 2,
+    };
+    if (
+      point.x < 0 ||
+      point.x > state.window.innerWidth ||
+      point.y < 0 ||
+      point.y > state.window.innerHeight
+    ) {
+      return null;
+    }
+    return {
+      name: element.nodeName,
+      content: (element.textContent ?? "").trim().replace(/\s+/g, " "),
+      point,
+    };
+  }
+
+  function targets(elements: Iterable<Element>): Target[] {
+    const result: Target[] = [];
+    const added = new Set<Element>();
+    for (const element of elements) {
+      if (added.has(element)) continue;
+      if (
+        element === state.document.documentElement ||
+        element === state.document.body
+      ) {
+        continue;
+      }
+      added.add(element);
+      const found = target(element);
+      if (found) result.push(found);
+    }
+    return result;
+  }
+
+  // Selectors of elements whose hovering styles some other element, like
+  // `.menu` in `.menu:hover > .submenu { display: block }`.
+  const hoverSelectors: string[] = [];
+  function collectHoverSelectors(rules: CSSRuleList) {
+    for (const rule of Array.from(rules)) {
+      if (rule instanceof CSSStyleRule) {
+        for (const selector of rule.selectorText.split(",")) {
+          const match = selector.match(/^(.*?):hover\s*[\s>+~]\s*\S/);
+          if (match && match[1]!.trim()) hoverSelectors.push(match[1]!.trim());
+        }
+      } else if (rule instanceof CSSGroupingRule) {
+        collectHoverSelectors(rule.cssRules);
+      }
+    }
+  }
+  for (const sheet of Array.from(state.document.styleSheets)) {
+    try {
+      collectHoverSelectors(sheet.cssRules);
+    } catch {
+      // Cross-origin stylesheets can't be read.
+    }
+  }
+
+  const hoverElements: Element[] = [
+    ...Array.from(
+      state.document.querySelectorAll(
+        "[aria-haspopup]:not([aria-haspopup=false]),[onmouseenter],[onmouseover]",
+      ),
+    ),
+    ...(listeners?.elements("mouseenter") ?? []),
+    ...(listeners?.elements("mouseover") ?? []),
+  ];
+  for (const selector of hoverSelectors) {
+    try {
+      hoverElements.push(
+        ...Array.from(state.document.querySelectorAll(selector)),
+      );
+    } catch {
+      // Selectors with pseudo-elements and the like aren't queryable.
+    }
+  }
+
+  const contextElements: Element[] = [
+    ...Array.from(state.document.querySelectorAll("[oncontextmenu]")),
+    ...(listeners?.elements("contextmenu") ?? []),
+  ];
+
+  return {
+    hover: targets(hoverElements),
+    context: targets(contextElements),
+  };
+});
+
+export const hovers = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  if (touchEnabled.current) return [];
+  return pointerTargets.current.hover.map(
+    ({ name, content, point }) =>
+      ({
+        Hover: { name, content, point },
+      }) as Action,
+  );
+});
+
+export const contextClicks = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  if (touchEnabled.current) return [];
+  return pointerTargets.current.context.map(
+    ({ name, content, point }) =>
+      ({
+        ContextClick: { name, content, point },
+      }) as Action,
+  );
+});
+
+// Drags
+
+const dragTargets = extract((state) => {
+  type Point = { x: number; y: number };
+  type RangeTarget = { left: number; right: number; y: number; thumb: number };
+
+  function center(element: Element): Point | null {
+    const rect = element.getBoundingClientRect();
+    if (rect.width <= 0 || rect.height <= 0) return null;
+    const point = {
+      x: rect.left + rect.width / 2,
+      y: rect.top + rect.height / 2,
+    };
+    if (
+      point.x < 0 ||
+      point.x > state.window.innerWidth ||
+      point.y < 0 ||
+      point.y > state.window.innerHeight
+    ) {
+      return null;
+    }
+    return point;
+  }
+
+  const draggables: Point[] = [];
+  for (const element of Array.from(
+    state.document.querySelectorAll('[draggable="true"]'),
+  )) {
+    const point = center(element);
+    if (point) draggables.push(point);
+  }
+
+  const ranges: RangeTarget[] = [];
+  for (const element of Array.from(
+    state.document.querySelectorAll('input[type="range"]'),
+  )) {
+    if (!(element instanceof HTMLInputElement) || element.disabled) continue;
+    const point = center(element);
+    if (!point) continue;
+    const rect = element.getBoundingClientRect();
+    const min = parseFloat(element.min || "0");
+    const max = parseFloat(element.max || "100");
+    const fraction =
+      max > min ? (parseFloat(element.value) - min) / (max - min) : 0;
+    ranges.push({
+      left: rect.left,
+      right: rect.right,
+      y: point.y,
+      thumb: rect.left + rect.width * fraction,
+    });
+  }
+
+  return { draggables, ranges };
+});
+
+// Drags `[draggable=true]` elements onto each other or anywhere in the
+// viewport, and moves the thumbs of range inputs.
+export const drags = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  const { draggables, ranges } = dragTargets.current;
+  const steps = integers().min(5).max(20).generate();
+  const viewportPoint = () => ({
+    x: integers().min(0).max(window.current.inner.width).generate(),
+    y: integers().min(0).max(window.current.inner.height).generate(),
+  });
+
+  const result: Action[] = [];
+  for (const start of draggables) {
+    const others = draggables.filter((other) => other !== start);
+    const to = from([...others, viewportPoint()]).generate();
+    result.push({ Drag: { from: start, to, steps } });
+  }
+  for (const range of ranges) {
+    // The upper bound of `integers()` is exclusive, and must be above the
+    // lower bound even for very narrow sliders.
+    const left = Math.ceil(range.left);
+    const right = Math.max(left + 1, Math.floor(range.right));
+    const to = { x: integers().min(left).max(right).generate(), y: range.y };
+    result.push({ Drag: { from: { x: range.thumb, y: range.y }, to, steps } });
+  }
+  return result;
+});
+
+// Touch gestures
+
+export const longPresses = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  if (!touchEnabled.current) return [];
+  const durationMillis = integers().min(500).max(1500).generate();
   return clickablePoints.current.map(
     ({ name, content, point }) =>
       ({
-        Click: { name, content, point },
+        LongPress: { name, content, point, durationMillis },
       }) as Action,
   );
 });
 
+export const pinches = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  if (!touchEnabled.current) return [];
+  const origin = {
+    x: window.current.inner.width / 2,
+    y: window.current.inner.height / 2,
+  };
+  return [
+    { Pinch: { origin, scale: 2 } } as Action,
+    { Pinch: { origin, scale: 0.5 } } as Action,
+  ];
+});
+
 // Inputs
 
 const activeInput = extract((state) => {
@@ -288,9 +556,108 @@ const activeInput = extract((state) => {
     return element.type;
   }
 
+  if (element instanceof HTMLElement && element.isContentEditable) {
+    return "contenteditable";
+  }
+
   return null;
 });
 
+// The validation constraints of the focused input or textarea.
+const activeConstraints = extract((state) => {
+  const element = state.document.activeElement;
+  if (
+    !(element instanceof HTMLInputElement) &&
+    !(element instanceof HTMLTextAreaElement)
+  ) {
+    return null;
+  }
+  const input = element instanceof HTMLInputElement ? element : null;
+  return {
+    length: element.value.length,
+    required: element.required,
+    // -1 when not set.
+    minLength: element.minLength,
+    maxLength: element.maxLength,
+    pattern: input?.pattern ?? "",
+    min: input?.min ?? "",
+    max: input?.max ?? "",
+    step: input?.step ?? "",
+  };
+});
+
+type Constraints = NonNullable<typeof activeConstraints.current>;
+
+function fullyMatches(pattern: string, value: string): boolean {
+  try {
+    return new RegExp(`^(?:${pattern})$`, "u").test(value);
+  } catch {
+    return false;
+  }
+}
+
+// Text on and just past the boundaries of the length and pattern
+// constraints, so that both valid and invalid submissions are tried.
+function constrainedText(constraints: Constraints): string[] {
+  const { minLength, maxLength, pattern } = constraints;
+  const ofLength = (length: number) =>
+    strings()
+      .minSize(length)
+      .maxSize(length + 1)
+      .generate();
+  const values: string[] = [];
+
+  if (minLength > 0) {
+    values.push(ofLength(minLength));
+    if (minLength > 1) values.push(ofLength(minLength - 1));
+  }
+  // Typing past the maximum length is cut off by the browser, so there's no
+  // point in generating longer text.
+  if (maxLength > 0) {
+    values.push(ofLength(maxLength));
+  }
+  if (pattern !== "") {
+    try {
+      const match = matching(pattern).generate();
+      if (fullyMatches(pattern, match)) values.push(match);
+      const mismatch = [match + "!", match.slice(1), ofLength(8)].find(
+        (candidate) => candidate !== "" && !fullyMatches(pattern, candidate),
+      );
+      if (mismatch !== undefined) values.push(mismatch);
+    } catch {
+      // The pattern uses syntax `matching` doesn't support.
+    }
+  }
+
+  return values;
+}
+
+// Numbers on the `min` and `max` boundaries and just outside them, and ones
+// that don't fit `step`.
+function constrainedNumbers(constraints: Constraints): string[] {
+  const parse = (value: string) =>
+    value === "" ? null : Number.isFinite(+value) ? +value : null;
+  const min = parse(constraints.min);
+  const max = parse(constraints.max);
+  const step = parse(constraints.step) ?? 1;
+  const values: number[] = [];
+
+  if (min !== null) {
+    values.push(min, min - step);
+    if (max === null || min + step <= max) values.push(min + step);
+  }
+  if (max !== null) {
+    values.push(max, max + step);
+  }
+  if (constraints.step !== "any") {
+    values.push((min ?? 0) + step / 2);
+  }
+
+  return values.map((value) => value.toString());
+}
+
+const TEXT_INPUT_TYPES = ["text", "search", "tel", "url", "password"];
+
 export const inputs = actions(() => {
   if (contentType.current !== "text/html") return [];
   const type = activeInput.current;
@@ -299,44 +666,290 @@ export const inputs = actions(() => {
   if (type === "file") return [];
 
   const delayMillis = integers().min(1).max(100).generate();
+  const constraints = activeConstraints.current;
+
+  let random: string;
+  let constrained: string[] = [];
+  if (
+    type === "textarea" ||
+    type === "contenteditable" ||
+    TEXT_INPUT_TYPES.includes(type)
+  ) {
+    random = strings().minSize(1).generate();
+    if (constraints) constrained = constrainedText(constraints);
+  } else if (type === "email") {
+    random = emails().generate();
+    if (constraints) constrained = constrainedText(constraints);
+  } else if (type === "number") {
+    random = integers().min(0).max(10000).generate().toString();
+    if (constraints) constrained = constrainedNumbers(constraints);
+  } else {
+    return [];
+  }
+
+  const typeText = (text: string): Action => ({
+    TypeText: { text, delayMillis },
+  });
 
-  if (type === "textarea") {
-    return weighted([
-      [1, { PressKey: { code: keycodes().generate() } }],
-      [3, { TypeText: { text: strings().minSize(1).generate(), delayMillis } }],
-    ]).generate();
+  // Constrained values are only useful in an empty field, so when there's
+  // text already we sometimes select it all, for the next text (or a
+  // Backspace, leaving a required field empty) to replace.
+  const selectAll =
+    constraints !== null &&
+    constraints.length > 0 &&
+    (constrained.length > 0 || constraints.required);
+
+  const choices: [number, Action][] = [
+    [1, { PressKey: { code: keycodes().generate() } }],
+    [3, typeText(random)],
+  ];
+  if (constrained.length > 0) {
+    choices.push([3, typeText(from(constrained).generate())]);
   }
+  if (selectAll) {
+    choices.push([1, { PressKey: { code: 65, modifiers: { ctrl: true } } }]);
+  }
+  return weighted(choices).generate();
+});
+
+// Form controls
+
+const formControls = extract((state) => {
+  type Point = { x: number; y: number };
+  type Select = { selector: string; multiple: boolean; values: string[] };
+  type Toggle = { name: string; content: string; point: Point };
+  type ValueInput = {
+    selector: string;
+    type: string;
+    min: string;
+    max: string;
+    step: string;
+  };
+
+  // A selector matching only the given element, by id if it has one and by
+  // its path from the root element otherwise.
+  function selectorOf(element: Element): string {
+    if (element.id) return `#${CSS.escape(element.id)}`;
+    const path: string[] = [];
+    let current: Element = element;
+    while (current.parentElement) {
+      const parent: Element = current.parentElement;
+      const tag = current.nodeName.toLowerCase();
+      const siblings = Array.from(parent.children).filter(
+        (sibling) => sibling.nodeName === current.nodeName,
+      );
+      path.unshift(
+        siblings.length > 1
+          ? `${tag}:nth-of-type(${siblings.indexOf(current) + 1})`
+          : tag,
+      );
+      current = parent;
+    }
+    path.unshift(current.nodeName.toLowerCase());
+    return path.join(" > ");
+  }
+
+  function isShown(element: Element): boolean {
+    const style = state.window.getComputedStyle(element);
+    return style.display !== "none" && style.visibility !== "hidden";
+  }
+
+  function pointOf(element: Element): Point | null {
+    const rect = element.getBoundingClientRect();
+    if (rect.width <= 0 || rect.height <= 0) return null;
+    const point = {
+      x: rect.left + rect.width / 2,
+      y: rect.top + rect.height / 2,
+    };
+    if (
+      point.x < 0 ||
+      point.x > state.window.innerWidth ||
+      point.y < 0 ||
+      point.y > state.window.innerHeight
+    ) {
+      return null;
+    }
+    return point;
+  }
+
+  const selects: Select[] = [];
+  for (const element of Array.from(state.document.querySelectorAll("select"))) {
+    if (element.disabled || !isShown(element)) continue;
+    const values = Array.from(element.options)
+      .filter((option) => !option.disabled)
+      .map((option) => option.value);
+    if (values.length === 0) continue;
+    selects.push({
+      selector: selectorOf(element),
+      multiple: element.multiple,
+      values,
+    });
+  }
+
+  // Checkboxes and radio buttons are often hidden and replaced by custom
+  // styling, in which case we click their label instead.
+  const toggles: Toggle[] = [];
+  for (const element of Array.from(
+    state.document.querySelectorAll(
+      'input[type="checkbox"],input[type="radio"]',
+    ),
+  )) {
+    if (!(element instanceof HTMLInputElement) || element.disabled) continue;
+    const label = element.labels?.[0] ?? null;
+    const target = [element, label].find(
+      (candidate): candidate is HTMLElement =>
+        !!candidate && isShown(candidate) && pointOf(candidate) !== null,
+    );
+    if (!target) continue;
+    toggles.push({
+      name: target.nodeName,
+      content: (label?.textContent ?? "").trim().replace(/\s+/g, " "),
+      point: pointOf(target)!,
+    });
+  }
+
+  const valueInputs: ValueInput[] = [];
+  for (const element of Array.from(
+    state.document.querySelectorAll(
+      ["range", "date", "datetime-local", "month", "week", "time", "color"]
+        .map((type) => `input[type="${type}"]`)
+        .join(","),
+    ),
+  )) {
+    if (!(element instanceof HTMLInputElement)) continue;
+    if (element.disabled || element.readOnly || !isShown(element)) continue;
+    valueInputs.push({
+      selector: selectorOf(element),
+      type: element.type,
+      min: element.min,
+      max: element.max,
+      step: element.step,
+    });
+  }
+
+  return { selects, toggles, valueInputs };
+});
+
+// Picks one option in single selects, and any number of options in
+// multi-selects.
+export const selects = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  return formControls.current.selects.map(({ selector, multiple, values }) => {
+    const selected = multiple
+      ? values.filter(() => integers().min(0).max(2).generate() === 1)
+      : [from(values).generate()];
+    return { SelectOptions: { selector, values: selected } } as Action;
+  });
+});
+
+export const toggles = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  return formControls.current.toggles.map(
+    ({ name, content, point }) =>
+      ({
+        Click: { name, content, point },
+      }) as Action,
+  );
+});
+
+const pad = (value: number, length = 2) =>
+  value.toString().padStart(length, "0");
+
+// A random integer between `min` and `max`, both inclusive.
+const between = (min: number, max: number) =>
+  max > min ? integers().min(min).max(max + 1).generate() : min;
+
+function randomInputValue(
+  type: string,
+  min: string,
+  max: string,
+  step: string,
+): string {
+  const DAY_MILLIS = 24 * 60 * 60 * 1000;
+  const parseDate = (value: string, fallback: string) => {
+    const time = Date.parse(value);
+    return Number.isNaN(time) ? Date.parse(fallback) : time;
+  };
+  const randomDate = () =>
+    new Date(
+      between(
+        Math.floor(parseDate(min, "1970-01-01") / DAY_MILLIS),
+        Math.floor(parseDate(max, "2037-12-31") / DAY_MILLIS),
+      ) * DAY_MILLIS,
+    );
+  const parseMinutes = (value: string, fallback: number) => {
+    const match = value.match(/^(\d\d):(\d\d)/);
+    return match ? parseInt(match[1]!) * 60 + parseInt(match[2]!) : fallback;
+  };
+  const randomTime = () => {
+    const minutes = between(
+      parseMinutes(min, 0),
+      parseMinutes(max, 23 * 60 + 59),
+    );
+    return `${pad(Math.floor(minutes / 60))}:${pad(minutes % 60)}`;
+  };
 
   switch (type) {
-    case "text":
-      return weighted([
-        [1, { PressKey: { code: keycodes().generate() } }],
-        [
-          3,
-          { TypeText: { text: strings().minSize(1).generate(), delayMillis } },
-        ],
-      ]).generate();
-    case "email":
-      return weighted([
-        [1, { PressKey: { code: keycodes().generate() } }],
-        [3, { TypeText: { text: emails().generate(), delayMillis } }],
-      ]).generate();
-    case "number":
-      return weighted([
-        [1, { PressKey: { code: keycodes().generate() } }],
-        [
-          3,
-          {
-            TypeText: {
-              text: integers().min(0).max(10000).generate().toString(),
-              delayMillis,
-            },
-          },
-        ],
-      ]).generate();
+    case "range": {
+      const low = min === "" ? 0 : parseFloat(min);
+      const high = max === "" ? 100 : parseFloat(max);
+      const increment =
+        step === "" || step === "any" ? 1 : parseFloat(step) || 1;
+      const steps = Math.max(0, Math.floor((high - low) / increment));
+      return (low + between(0, steps) * increment).toString();
+    }
+    case "date":
+      return randomDate().toISOString().slice(0, 10);
+    case "datetime-local":
+      return `${randomDate().toISOString().slice(0, 10)}T${randomTime()}`;
+    case "month":
+      return randomDate().toISOString().slice(0, 7);
+    case "week":
+      return `${randomDate().getUTCFullYear()}-W${pad(between(1, 52))}`;
+    case "time":
+      return randomTime();
     default:
-      return [];
+      return `#${between(0, 0xffffff).toString(16).padStart(6, "0")}`;
   }
+}
+
+// Sets range, date, time and color inputs to valid random values, as these
+// can't be typed into.
+export const inputValues = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  return formControls.current.valueInputs.map(
+    ({ selector, type, min, max, step }) =>
+      ({
+        SetInputValue: {
+          selector,
+          value: randomInputValue(type, min, max, step),
+        },
+      }) as Action,
+  );
+});
+
+// Keyboard shortcuts
+
+const editingShortcuts: Action[] = [
+  { PressKey: { code: 90, modifiers: { ctrl: true } } }, // Ctrl+Z
+  { PressKey: { code: 90, modifiers: { ctrl: true, shift: true } } }, // Ctrl+Shift+Z
+  { PressKey: { code: 89, modifiers: { ctrl: true } } }, // Ctrl+Y
+  { PressKey: { code: 65, modifiers: { ctrl: true } } }, // Ctrl+A
+  { PressKey: { code: 67, modifiers: { ctrl: true } } }, // Ctrl+C
+  { PressKey: { code: 88, modifiers: { ctrl: true } } }, // Ctrl+X
+  { PressKey: { code: 86, modifiers: { ctrl: true } } }, // Ctrl+V
+];
+
+const focusShortcuts: Action[] = [
+  { PressKey: { code: 9, modifiers: { shift: true } } }, // Shift+Tab
+];
+
+// Common shortcut chords: undo/redo, select-all and clipboard shortcuts in
+// a focused text input, and moving focus backwards anywhere.
+export const shortcuts = actions(() => {
+  if (contentType.current !== "text/html") return [];
+  if (activeInput.current === null) return focusShortcuts;
+  return [...editingShortcuts, ...focusShortcuts];
 });
 
 // Navigation
@@ -362,3 +975,105 @@ export const navigation = weighted([
   [1, forward],
   [1, reload],
 ]);
+
+// Viewport
+
+export type Viewport = { width: number; height: number };
+
+// Common phone, tablet and desktop sizes, in portrait and landscape.
+export const breakpoints: Viewport[] = [
+  { width: 375, height: 667 },
+  { width: 667, height: 375 },
+  { width: 390, height: 844 },
+  { width: 768, height: 1024 },
+  { width: 1024, height: 768 },
+  { width: 1280, height: 800 },
+  { width: 1920, height: 1080 },
+];
+
+const viewport = extract((state) => ({
+  width: state.window.innerWidth,
+  height: state.window.innerHeight,
+}));
+
+// Resizes the viewport to one of the given sizes, other than the current one.
+export function resizesTo(viewports: Viewport[]): ActionGenerator {
+  return actions(() => {
+    const current = viewport.current;
+    return viewports
+      .filter(
+        ({ width, height }) =>
+          width !== current.width || height !== current.height,
+      )
+      .map(({ width, height }) => ({ Resize: { width, height } }) as Action);
+  });
+}
+
+export const resizes = resizesTo(breakpoints);
+
+// Tabs
+
+const openTabs = extract((state) => state.tabs);
+
+// Switches to background tabs, and closes the active one, when more than one
+// tab is open.
+export const tabs = actions(() => {
+  const open = openTabs.current;
+  if (open.length < 2) return [];
+  return open.map((tab, index): Action =>
+    tab.active ? { CloseTab: { index } } : { SwitchTab: { index } },
+  );
+});
+
+// Network faults
+
+const apiRequestUrls = extract((state) =>
+  state.network
+    .filter(
+      (request) =>
+        request.resourceType === "Fetch" || request.resourceType === "XHR",
+    )
+    .map((request) => request.url.split(/[?#]/)[0]!),
+);
+
+// Makes one of the next requests to an API endpoint requested in the
+// current state fail, get a server error, or be delayed. Not part of the
+// default actions, as it's meant for testing error handling specifically.
+export const faults = actions(() => {
+  if (apiRequestUrls.current.length === 0) return [];
+  const urlPattern = from(apiRequestUrls.current).generate() + "*";
+
+  return weighted([
+    [1, { InjectFault: { urlPattern, fault: "Fail" } }],
+    [
+      1,
+      {
+        InjectFault: {
+          urlPattern,
+          fault: { Status: { code: from([500, 502, 503]).generate() } },
+        },
+      },
+    ],
+    [
+      1,
+      {
+        InjectFault: {
+          urlPattern,
+          fault: {
+            Delay: { millis: integers().min(500).max(5000).generate() },
+          },
+        },
+      },
+    ],
+  ]).generate();
+});
+
+// Connectivity
+
+const online = extract((state) => state.connectivity.online);
+
+// Takes the pages offline, or back online when they are offline. Not part of
+// the default actions, as most apps aren't meant to work offline.
+export const connectivity = actions((): Action[] =>
+  online.current ? ["GoOffline"] : ["GoOnline"],
+);
@@ -79,3 +79,73 @@ const consoleErrors = extract((state) =>
 export const noConsoleErrors = always(
   () => consoleErrors.current?.length === 0,
 );
+
+// The accessibility properties below aren't in the defaults, as they need the
+// tree, which is only captured with `--accessibility-tree`. Without it, they
+// fail rather than pass without checking anything.
+const accessibilityTreeCaptured = extract(
+  (state) => state.accessibilityTree !== null,
+);
+
+// Roles of elements users interact with, which assistive technology can only
+// announce meaningfully if they have a name.
+const interactiveRoles = new Set([
+  "button",
+  "link",
+  "textbox",
+  "searchbox",
+  "checkbox",
+  "radio",
+  "switch",
+  "combobox",
+  "listbox",
+  "menuitem",
+  "tab",
+  "slider",
+  "spinbutton",
+]);
+
+const unnamedInteractiveElements = extract((state) =>
+  (state.accessibilityTree ?? [])
+    .filter(
+      (node) =>
+        !node.ignored &&
+        node.role !== null &&
+        interactiveRoles.has(node.role) &&
+        !node.name?.trim(),
+    )
+    .map((node) => node.role),
+);
+
+export const interactiveElementsHaveNames = always(
+  () =>
+    accessibilityTreeCaptured.current &&
+    unnamedInteractiveElements.current.length === 0,
+);
+
+// Reasons for a node to be ignored that mean it can't be seen.
+const hiddenReasons = new Set([
+  "notRendered",
+  "notVisible",
+  "ariaHiddenElement",
+  "ariaHiddenSubtree",
+  "inertElement",
+  "inertSubtree",
+]);
+
+const hiddenFocusedElements = extract((state) =>
+  (state.accessibilityTree ?? [])
+    .filter(
+      (node) =>
+        node.properties["focused"] === true &&
+        (node.properties["hidden"] === true ||
+          node.ignoredReasons.some((reason) => hiddenReasons.has(reason))),
+    )
+    .map((node) => node.role),
+);
+
+export const focusNeverOnHiddenElement = always(
+  () =>
+    accessibilityTreeCaptured.current &&
+    hiddenFocusedElements.current.length === 0,
+);
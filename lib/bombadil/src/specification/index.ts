@@ -2,6 +2,7 @@ import {
   type JSON,
   ExtractorCell,
   Runtime,
+  type SetupScript,
   type TimeUnit,
   type Cell,
 } from "@antithesishq/bombadil/internal";
@@ -10,12 +11,14 @@ import {
 export const runtime = new Runtime<State>();
 
 // Reexports
-export { type Cell } from "@antithesishq/bombadil/internal";
+export { type Cell, type SetupScript } from "@antithesishq/bombadil/internal";
 export {
   actions,
   weighted,
   type Action,
   type Generator,
+  type KeyModifiers,
+  type NetworkFault,
   type Point,
   ActionGenerator,
   from,
@@ -23,6 +26,7 @@ export {
   emails,
   integers,
   keycodes,
+  matching,
 } from "@antithesishq/bombadil/actions";
 
 import type { Action } from "@antithesishq/bombadil/actions";
@@ -223,6 +227,23 @@ export function extract<T extends JSON>(query: (state: State) => T): Cell<T> {
   return new ExtractorCell<T, State>(runtime, query);
 }
 
+// Setup
+
+// Steps run from the origin before exploring, at the start of the test and of
+// every episode: either a fixed sequence of actions, or a script run in the
+// page.
+export class Setup {
+  constructor(public steps: Action[] | SetupScript) {
+    if (typeof steps === "function") {
+      runtime.registerSetupScript(steps);
+    }
+  }
+}
+
+export function setup(steps: Action[] | SetupScript): Setup {
+  return new Setup(steps);
+}
+
 export interface State {
   document: HTMLDocument;
   window: Window;
@@ -250,9 +271,74 @@ export interface State {
     }[];
   };
   console: ConsoleEntry[];
+  network: NetworkRequest[];
+  tabs: Tab[];
+  connectivity: Connectivity;
+  // Whether touch input is emulated instead of a mouse, as with `--mobile`.
+  touch: boolean;
+  storage: StorageState;
+  accessibilityTree: AccessibilityNode[] | null;
   lastAction: Action | null;
 }
 
+// The open pages, in the order they were opened. Actions are applied to the
+// active one, and `document` and `window` belong to it.
+export type Tab = {
+  url: string;
+  title: string;
+  active: boolean;
+};
+
+// Whether the pages are online, as toggled by the `GoOffline` and `GoOnline`
+// actions, and how their network is throttled (null when it isn't).
+export type Connectivity = {
+  online: boolean;
+  throttling: {
+    latencyMillis: number;
+    downloadKbps: number;
+    uploadKbps: number;
+  } | null;
+};
+
+// The cookies visible to the active tab, including HttpOnly ones, and the web
+// storage of its origin.
+export type StorageState = {
+  cookies: Cookie[];
+  localStorage: Record<string, string>;
+  sessionStorage: Record<string, string>;
+  indexedDbDatabases: string[];
+};
+
+export type Cookie = {
+  name: string;
+  value: string;
+  domain: string;
+  path: string;
+  // Seconds since the epoch, or null for session cookies.
+  expires: number | null;
+  httpOnly: boolean;
+  secure: boolean;
+  sameSite: "Strict" | "Lax" | "None" | null;
+};
+
+// A node of the active tab's accessibility tree, which is only captured when
+// enabled (null otherwise). Nodes refer to their parent and children by id.
+export type AccessibilityNode = {
+  id: string;
+  parentId: string | null;
+  childIds: string[];
+  role: string | null;
+  name: string | null;
+  description: string | null;
+  value: string | null;
+  // Ignored nodes, like hidden or purely presentational ones, aren't exposed
+  // to assistive technology.
+  ignored: boolean;
+  ignoredReasons: string[];
+  // Properties like `focusable`, `focused`, `disabled` or `checked`.
+  properties: Record<string, JSON>;
+};
+
 export type NavigationEntry = {
   id: number;
   title: string;
@@ -264,3 +350,14 @@ export type ConsoleEntry = {
   level: "warning" | "error";
   args: JSON[];
 };
+
+// Requests that finished or failed since the previous state.
+export type NetworkRequest = {
+  timestamp: number;
+  url: string;
+  method: string;
+  resourceType: string | null;
+  status: number | null;
+  durationMillis: number;
+  failure: string | null;
+};
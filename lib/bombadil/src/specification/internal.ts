@@ -52,11 +52,17 @@ export class ExtractorCell<T extends JSON, S> implements Cell<T> {
   }
 }
 
+export type SetupScript = (page: {
+  document: Document;
+  window: Window;
+}) => Promise<void>;
+
 export class Runtime<S> {
   extractors: ExtractorCell<any, S>[] = [];
   private extractingDepth: number = 0;
   private tracking = false;
   private accesses = new Set<number>();
+  private setupScript: SetupScript | null = null;
 
   registerExtractor(cell: ExtractorCell<any, S>): number {
     const index = this.extractors.length;
@@ -64,6 +70,22 @@ export class Runtime<S> {
     return index;
   }
 
+  registerSetupScript(script: SetupScript): void {
+    this.setupScript = script;
+  }
+
+  async runSetup(page: {
+    document: Document;
+    window: Window;
+  }): Promise<void> {
+    if (this.setupScript === null) {
+      throw new Error(
+        "no setup script is registered (this is a bug in the runtime)",
+      );
+    }
+    await this.setupScript(page);
+  }
+
   startTracking(): void {
     this.tracking = true;
     this.accesses.clear();
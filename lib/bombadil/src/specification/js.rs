@@ -7,12 +7,18 @@ use boa_engine::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::browser::actions::BrowserAction;
+use crate::browser::actions::{BrowserAction, KeyModifiers, NetworkFault};
 use crate::geometry::Point;
 use crate::specification::domain::{BombadilDomain, Snapshot};
 use crate::specification::result::{Result, SpecificationError};
 use bombadil_ltl::syntax::Syntax;
 
+/// One day, long enough for session expiry and the like.
+const MAX_ADVANCE_CLOCK_MILLIS: f64 = 86_400_000.0;
+
+/// Larger than any real screen, but small enough to screenshot.
+const MAX_VIEWPORT_LENGTH: f64 = 10_000.0;
+
 /// TypeScript-friendly action representation with camelCase and f64 for numbers.
 /// This matches the JSON that comes from the JavaScript specification layer.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -40,6 +46,8 @@ pub enum JsAction {
     #[serde(rename_all = "camelCase")]
     PressKey {
         code: f64,
+        #[serde(default)]
+        modifiers: KeyModifiers,
     },
     #[serde(rename_all = "camelCase")]
     ScrollUp {
@@ -58,6 +66,79 @@ pub enum JsAction {
         selector: String,
         files: Vec<String>,
     },
+    SelectOptions {
+        selector: String,
+        values: Vec<String>,
+    },
+    SetInputValue {
+        selector: String,
+        value: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    InjectFault {
+        url_pattern: String,
+        fault: JsNetworkFault,
+    },
+    GoOffline,
+    GoOnline,
+    AdvanceClock {
+        millis: f64,
+    },
+    Resize {
+        width: f64,
+        height: f64,
+    },
+    SwitchTab {
+        index: f64,
+    },
+    CloseTab {
+        index: f64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Tap {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    #[serde(rename_all = "camelCase")]
+    LongPress {
+        name: String,
+        content: Option<String>,
+        point: Point,
+        duration_millis: f64,
+    },
+    Swipe {
+        start: Point,
+        end: Point,
+    },
+    Pinch {
+        origin: Point,
+        scale: f64,
+    },
+    Drag {
+        from: Point,
+        to: Point,
+        steps: f64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Hover {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+    #[serde(rename_all = "camelCase")]
+    ContextClick {
+        name: String,
+        content: Option<String>,
+        point: Point,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum JsNetworkFault {
+    Fail,
+    Status { code: f64 },
+    Delay { millis: f64 },
 }
 
 impl JsAction {
@@ -115,7 +196,7 @@ impl JsAction {
                     delay_millis: delay_millis as u64,
                 }
             }
-            JsAction::PressKey { code } => {
+            JsAction::PressKey { code, modifiers } => {
                 if !code.is_finite()
                     || !(0.0..=255.0).contains(&code)
                     || code.fract() != 0.0
@@ -125,7 +206,10 @@ impl JsAction {
                         code
                     );
                 }
-                BrowserAction::PressKey { code: code as u8 }
+                BrowserAction::PressKey {
+                    code: code as u8,
+                    modifiers,
+                }
             }
             JsAction::ScrollUp { origin, distance } => {
                 BrowserAction::ScrollUp { origin, distance }
@@ -137,10 +221,171 @@ impl JsAction {
             JsAction::SetFileInputFiles { selector, files } => {
                 BrowserAction::SetFileInputFiles { selector, files }
             }
+            JsAction::SelectOptions { selector, values } => {
+                BrowserAction::SelectOptions { selector, values }
+            }
+            JsAction::SetInputValue { selector, value } => {
+                BrowserAction::SetInputValue { selector, value }
+            }
+            JsAction::InjectFault { url_pattern, fault } => {
+                let fault = match fault {
+                    JsNetworkFault::Fail => NetworkFault::Fail,
+                    JsNetworkFault::Status { code } => {
+                        if !(100.0..=599.0).contains(&code)
+                            || code.fract() != 0.0
+                        {
+                            bail!(
+                                "code must be an integer between 100 and 599, got {}",
+                                code
+                            );
+                        }
+                        NetworkFault::Status { code: code as u16 }
+                    }
+                    JsNetworkFault::Delay { millis } => {
+                        if !millis.is_finite() || millis < 0.0 {
+                            bail!(
+                                "millis must be a non-negative finite number, got {}",
+                                millis
+                            );
+                        }
+                        if millis > 60_000.0 {
+                            bail!(
+                                "millis must be at most 60000, got {}",
+                                millis
+                            );
+                        }
+                        NetworkFault::Delay {
+                            millis: millis as u64,
+                        }
+                    }
+                };
+                BrowserAction::InjectFault { url_pattern, fault }
+            }
+            JsAction::GoOffline => BrowserAction::GoOffline,
+            JsAction::GoOnline => BrowserAction::GoOnline,
+            JsAction::AdvanceClock { millis } => {
+                if !millis.is_finite() || millis < 1.0 {
+                    bail!(
+                        "millis must be a finite number of at least 1, got {}",
+                        millis
+                    );
+                }
+                if millis > MAX_ADVANCE_CLOCK_MILLIS {
+                    bail!(
+                        "millis must be at most {}, got {}",
+                        MAX_ADVANCE_CLOCK_MILLIS,
+                        millis
+                    );
+                }
+                BrowserAction::AdvanceClock {
+                    millis: millis as u64,
+                }
+            }
+            JsAction::Resize { width, height } => BrowserAction::Resize {
+                width: viewport_length(width)?,
+                height: viewport_length(height)?,
+            },
+            JsAction::SwitchTab { index } => BrowserAction::SwitchTab {
+                index: tab_index(index)?,
+            },
+            JsAction::CloseTab { index } => BrowserAction::CloseTab {
+                index: tab_index(index)?,
+            },
+            JsAction::Tap {
+                name,
+                content,
+                point,
+            } => BrowserAction::Tap {
+                name,
+                content,
+                point,
+            },
+            JsAction::LongPress {
+                name,
+                content,
+                point,
+                duration_millis,
+            } => {
+                if !duration_millis.is_finite()
+                    || !(0.0..=5000.0).contains(&duration_millis)
+                {
+                    bail!(
+                        "durationMillis must be between 0 and 5000, got {}",
+                        duration_millis
+                    );
+                }
+                BrowserAction::LongPress {
+                    name,
+                    content,
+                    point,
+                    duration_millis: duration_millis as u64,
+                }
+            }
+            JsAction::Swipe { start, end } => {
+                BrowserAction::Swipe { start, end }
+            }
+            JsAction::Pinch { origin, scale } => {
+                if !scale.is_finite() || !(0.1..=10.0).contains(&scale) {
+                    bail!("scale must be between 0.1 and 10, got {}", scale);
+                }
+                BrowserAction::Pinch { origin, scale }
+            }
+            JsAction::Drag { from, to, steps } => {
+                if !(1.0..=100.0).contains(&steps) || steps.fract() != 0.0 {
+                    bail!(
+                        "steps must be an integer between 1 and 100, got {}",
+                        steps
+                    );
+                }
+                BrowserAction::Drag {
+                    from,
+                    to,
+                    steps: steps as u32,
+                }
+            }
+            JsAction::Hover {
+                name,
+                content,
+                point,
+            } => BrowserAction::Hover {
+                name,
+                content,
+                point,
+            },
+            JsAction::ContextClick {
+                name,
+                content,
+                point,
+            } => BrowserAction::ContextClick {
+                name,
+                content,
+                point,
+            },
         })
     }
 }
 
+fn viewport_length(length: f64) -> anyhow::Result<u16> {
+    if !(1.0..=MAX_VIEWPORT_LENGTH).contains(&length) || length.fract() != 0.0 {
+        anyhow::bail!(
+            "viewport width and height must be integers between 1 and {}, got {}",
+            MAX_VIEWPORT_LENGTH,
+            length
+        );
+    }
+    Ok(length as u16)
+}
+
+fn tab_index(index: f64) -> anyhow::Result<u32> {
+    if !(0.0..=u32::MAX as f64).contains(&index) || index.fract() != 0.0 {
+        anyhow::bail!(
+            "tab index must be a non-negative integer, got {}",
+            index
+        );
+    }
+    Ok(index as u32)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RuntimeFunction {
     pub object: JsObject,
@@ -303,6 +548,7 @@ pub struct BombadilExports {
     pub eventually: JsValue,
     pub runtime: JsObject,
     pub action_generator: JsValue,
+    pub setup: JsValue,
 }
 
 impl BombadilExports {
@@ -334,6 +580,7 @@ impl BombadilExports {
                 ),
             )?,
             action_generator: get_export("ActionGenerator")?,
+            setup: get_export("Setup")?,
         })
     }
 
@@ -363,6 +610,7 @@ impl BombadilExports {
                 ),
             )?,
             action_generator: get_export("ActionGenerator")?,
+            setup: get_export("Setup")?,
         })
     }
 }
@@ -455,8 +703,29 @@ mod tests {
         let json = r#"{"PressKey": {"code": 13.0}}"#;
         let action: JsAction = serde_json::from_str(json).unwrap();
         match action {
-            JsAction::PressKey { code } => {
+            JsAction::PressKey { code, modifiers } => {
                 assert_eq!(code, 13.0);
+                assert_eq!(modifiers, KeyModifiers::default());
+            }
+            _ => panic!("expected PressKey"),
+        }
+    }
+
+    #[test]
+    fn test_to_browser_action_converts_key_chords() {
+        let json =
+            r#"{"PressKey": {"code": 90.0, "modifiers": {"ctrl": true}}}"#;
+        let action: JsAction = serde_json::from_str(json).unwrap();
+        match action.to_browser_action().unwrap() {
+            BrowserAction::PressKey { code, modifiers } => {
+                assert_eq!(code, 90);
+                assert_eq!(
+                    modifiers,
+                    KeyModifiers {
+                        ctrl: true,
+                        ..KeyModifiers::default()
+                    }
+                );
             }
             _ => panic!("expected PressKey"),
         }
@@ -479,7 +748,10 @@ mod tests {
 
     #[test]
     fn test_to_browser_action_validates_code_range() {
-        let js_action = JsAction::PressKey { code: 256.0 };
+        let js_action = JsAction::PressKey {
+            code: 256.0,
+            modifiers: KeyModifiers::default(),
+        };
         let result = js_action.to_browser_action();
         assert!(result.is_err());
         assert!(
@@ -489,12 +761,110 @@ mod tests {
                 .contains("between 0 and 255")
         );
 
-        let js_action = JsAction::PressKey { code: 13.5 };
+        let js_action = JsAction::PressKey {
+            code: 13.5,
+            modifiers: KeyModifiers::default(),
+        };
         let result = js_action.to_browser_action();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("integer"));
     }
 
+    #[test]
+    fn test_to_browser_action_converts_faults() {
+        let json = r#"{"InjectFault": {"urlPattern": "*/api/*", "fault": {"Status": {"code": 503.0}}}}"#;
+        let action: JsAction = serde_json::from_str(json).unwrap();
+        match action.to_browser_action().unwrap() {
+            BrowserAction::InjectFault { url_pattern, fault } => {
+                assert_eq!(url_pattern, "*/api/*");
+                assert_eq!(fault, NetworkFault::Status { code: 503 });
+            }
+            _ => panic!("expected InjectFault"),
+        }
+
+        let json = r#"{"InjectFault": {"urlPattern": "*", "fault": "Fail"}}"#;
+        let action: JsAction = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            action.to_browser_action().unwrap(),
+            BrowserAction::InjectFault {
+                fault: NetworkFault::Fail,
+                ..
+            }
+        ));
+
+        let js_action = JsAction::InjectFault {
+            url_pattern: "*".to_string(),
+            fault: JsNetworkFault::Status { code: 42.0 },
+        };
+        let result = js_action.to_browser_action();
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("between 100 and 599")
+        );
+    }
+
+    #[test]
+    fn test_to_browser_action_validates_advance_clock() {
+        let json = r#"{"AdvanceClock": {"millis": 60000.0}}"#;
+        let action: JsAction = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            action.to_browser_action().unwrap(),
+            BrowserAction::AdvanceClock { millis: 60_000 }
+        ));
+
+        let js_action = JsAction::AdvanceClock { millis: f64::NAN };
+        assert!(js_action.to_browser_action().is_err());
+
+        let js_action = JsAction::AdvanceClock { millis: 1e12 };
+        assert!(js_action.to_browser_action().is_err());
+
+        let js_action = JsAction::AdvanceClock { millis: 0.0 };
+        assert!(js_action.to_browser_action().is_err());
+    }
+
+    #[test]
+    fn test_to_browser_action_validates_resize() {
+        let json = r#"{"Resize": {"width": 375.0, "height": 667.0}}"#;
+        let action: JsAction = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            action.to_browser_action().unwrap(),
+            BrowserAction::Resize {
+                width: 375,
+                height: 667
+            }
+        ));
+
+        let js_action = JsAction::Resize {
+            width: 0.0,
+            height: 667.0,
+        };
+        assert!(js_action.to_browser_action().is_err());
+
+        let js_action = JsAction::Resize {
+            width: 375.0,
+            height: 1e6,
+        };
+        assert!(js_action.to_browser_action().is_err());
+    }
+
+    #[test]
+    fn test_to_browser_action_validates_tab_index() {
+        let json = r#"{"SwitchTab": {"index": 1.0}}"#;
+        let action: JsAction = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            action.to_browser_action().unwrap(),
+            BrowserAction::SwitchTab { index: 1 }
+        ));
+
+        let js_action = JsAction::CloseTab { index: -1.0 };
+        assert!(js_action.to_browser_action().is_err());
+
+        let js_action = JsAction::CloseTab { index: 0.5 };
+        assert!(js_action.to_browser_action().is_err());
+    }
+
     #[test]
     fn test_to_browser_action_validates_delay_millis() {
         let js_action = JsAction::TypeText {
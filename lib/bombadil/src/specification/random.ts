@@ -122,3 +122,263 @@ export function integers(): IntegerGenerator {
 export function keycodes(): Generator<number> {
   return from([8, 9, 13, 27]);
 }
+
+// Pattern-matching strings
+
+type PatternNode =
+  | { kind: "chars"; chars: string[] }
+  | { kind: "sequence"; items: PatternNode[] }
+  | { kind: "alternation"; options: PatternNode[] }
+  | { kind: "repeat"; node: PatternNode; min: number; max: number };
+
+const PRINTABLE = Array.from({ length: 95 }, (_, i) =>
+  String.fromCharCode(32 + i),
+);
+const DIGITS = [..."0123456789"];
+const WORD = [
+  ..."abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_",
+  ...DIGITS,
+];
+
+// How many repetitions past the minimum an unbounded quantifier (`*`, `+`,
+// `{n,}`) generates at most.
+const MAX_EXTRA_REPETITIONS = 8;
+
+class PatternParser {
+  private position = 0;
+
+  constructor(private source: string) {}
+
+  parse(): PatternNode {
+    const node = this.alternation();
+    if (this.position < this.source.length) {
+      this.unsupported(`unmatched ${this.source[this.position]}`);
+    }
+    return node;
+  }
+
+  private unsupported(reason: string): never {
+    throw new SyntaxError(`unsupported pattern /${this.source}/: ${reason}`);
+  }
+
+  private peek(): string | undefined {
+    return this.source[this.position];
+  }
+
+  private next(): string {
+    const char = this.source[this.position++];
+    if (char === undefined) this.unsupported("unexpected end");
+    return char;
+  }
+
+  private alternation(): PatternNode {
+    const options = [this.sequence()];
+    while (this.peek() === "|") {
+      this.position++;
+      options.push(this.sequence());
+    }
+    return options.length === 1
+      ? options[0]!
+      : { kind: "alternation", options };
+  }
+
+  private sequence(): PatternNode {
+    const items: PatternNode[] = [];
+    while (
+      this.position < this.source.length &&
+      this.peek() !== "|" &&
+      this.peek() !== ")"
+    ) {
+      const atom = this.atom();
+      if (atom) items.push(this.quantified(atom));
+    }
+    return { kind: "sequence", items };
+  }
+
+  private atom(): PatternNode | null {
+    const char = this.next();
+    switch (char) {
+      case "^":
+      case "$":
+        return null;
+      case "(": {
+        if (this.source.startsWith("?:", this.position)) {
+          this.position += 2;
+        } else if (this.peek() === "?") {
+          this.unsupported("lookarounds and named groups");
+        }
+        const node = this.alternation();
+        if (this.next() !== ")") this.unsupported("unclosed group");
+        return node;
+      }
+      case "[":
+        return { kind: "chars", chars: this.characterClass() };
+      case ".":
+        return { kind: "chars", chars: PRINTABLE };
+      case "\\":
+        return { kind: "chars", chars: this.escape() };
+      case "*":
+      case "+":
+      case "?":
+      case "{":
+        return this.unsupported(`nothing to repeat with ${char}`);
+      default:
+        return { kind: "chars", chars: [char] };
+    }
+  }
+
+  private quantified(node: PatternNode): PatternNode {
+    let min: number;
+    let max: number;
+    const char = this.peek();
+    if (char === "?") {
+      [min, max] = [0, 1];
+    } else if (char === "*") {
+      [min, max] = [0, MAX_EXTRA_REPETITIONS];
+    } else if (char === "+") {
+      [min, max] = [1, 1 + MAX_EXTRA_REPETITIONS];
+    } else if (char === "{") {
+      const match = /^\{(\d+)(,(\d*))?\}/.exec(
+        this.source.slice(this.position),
+      );
+      if (!match) this.unsupported("malformed quantifier");
+      min = parseInt(match[1]!);
+      max =
+        match[2] === undefined
+          ? min
+          : match[3]
+            ? parseInt(match[3])
+            : min + MAX_EXTRA_REPETITIONS;
+      if (max < min) this.unsupported("quantifier range out of order");
+      this.position += match[0].length - 1;
+    } else {
+      return node;
+    }
+    this.position++;
+    // Lazy quantifiers match the same strings.
+    if (this.peek() === "?") this.position++;
+    return { kind: "repeat", node, min, max };
+  }
+
+  private escape(): string[] {
+    const char = this.next();
+    const complement = (chars: string[]) =>
+      PRINTABLE.filter((c) => !chars.includes(c));
+    switch (char) {
+      case "d":
+        return DIGITS;
+      case "D":
+        return complement(DIGITS);
+      case "w":
+        return WORD;
+      case "W":
+        return complement(WORD);
+      case "s":
+        return [" "];
+      case "S":
+        return complement([" "]);
+      case "n":
+        return ["\n"];
+      case "t":
+        return ["\t"];
+      case "r":
+        return ["\r"];
+      case "f":
+        return ["\f"];
+      case "v":
+        return ["\v"];
+      case "0":
+        if (/[0-9]/.test(this.peek() ?? "")) {
+          this.unsupported("octal escapes");
+        }
+        return ["\0"];
+      case "c": {
+        const letter = this.next();
+        if (!/[A-Za-z]/.test(letter)) {
+          this.unsupported(`escape \\c${letter}`);
+        }
+        return [String.fromCharCode(letter.charCodeAt(0) % 32)];
+      }
+      default:
+        if (/[1-9bBpPkux]/.test(char)) {
+          this.unsupported(`escape \\${char}`);
+        }
+        return [char];
+    }
+  }
+
+  private characterClass(): string[] {
+    const negated = this.peek() === "^";
+    if (negated) this.position++;
+    const chars = new Set<string>();
+    while (this.peek() !== "]") {
+      let char = this.next();
+      if (char === "[") this.unsupported("nested character classes");
+      if (char === "\\") {
+        const escaped = this.escape();
+        if (escaped.length > 1) {
+          escaped.forEach((c) => chars.add(c));
+          continue;
+        }
+        char = escaped[0]!;
+      }
+      if (this.peek() === "-" && this.source[this.position + 1] !== "]") {
+        this.position++;
+        let end = this.next();
+        if (end === "\\") end = this.escape()[0]!;
+        const [first, last] = [char.charCodeAt(0), end.charCodeAt(0)];
+        if (last < first) this.unsupported("character range out of order");
+        for (let code = first; code <= last; code++) {
+          chars.add(String.fromCharCode(code));
+        }
+      } else {
+        chars.add(char);
+      }
+    }
+    this.position++;
+    const result = negated
+      ? PRINTABLE.filter((c) => !chars.has(c))
+      : Array.from(chars);
+    if (result.length === 0) this.unsupported("empty character class");
+    return result;
+  }
+}
+
+function generateMatch(node: PatternNode): string {
+  switch (node.kind) {
+    case "chars":
+      return randomChoice(node.chars);
+    case "sequence":
+      return node.items.map(generateMatch).join("");
+    case "alternation":
+      return generateMatch(randomChoice(node.options));
+    case "repeat": {
+      const count = randomRange(node.min, node.max + 1);
+      return Array.from({ length: count }, () =>
+        generateMatch(node.node),
+      ).join("");
+    }
+  }
+}
+
+class PatternGenerator implements Generator<string> {
+  private root: PatternNode;
+
+  constructor(pattern: string) {
+    this.root = new PatternParser(pattern).parse();
+  }
+
+  generate() {
+    return generateMatch(this.root);
+  }
+}
+
+/**
+ * Generates strings that fully match a regular expression, like the
+ * `pattern` attribute of an input. Supports literals, character classes,
+ * groups, alternation and quantifiers, and throws a `SyntaxError` for
+ * anything else (like lookarounds and backreferences).
+ */
+export function matching(pattern: string): Generator<string> {
+  return new PatternGenerator(pattern);
+}
@@ -99,3 +99,71 @@ proptest! {
         prop_assert!(n.fract() == 0.0, "value {n} is not an integer");
     }
 }
+
+fn generate_matching(
+    context: &mut Context,
+    exports_obj: &JsObject,
+    pattern: &str,
+) -> Result<String, String> {
+    let matching = exports_obj
+        .get(js_string!("matching"), context)
+        .map_err(|e| e.to_string())?
+        .as_callable()
+        .ok_or_else(|| "matching is not a function".to_string())?;
+
+    let generator = matching
+        .call(
+            &JsValue::undefined(),
+            &[js_string!(pattern).into()],
+            context,
+        )
+        .map_err(|e| e.to_string())?;
+    let generator = generator
+        .as_object()
+        .ok_or_else(|| "matching did not return an object".to_string())?;
+    let generate = generator
+        .get(js_string!("generate"), context)
+        .map_err(|e| e.to_string())?
+        .as_callable()
+        .ok_or_else(|| "generate is not a function".to_string())?;
+
+    let result = generate
+        .call(&generator.clone().into(), &[], context)
+        .map_err(|e| e.to_string())?;
+    result
+        .as_string()
+        .map(|s| s.to_std_string_escaped())
+        .ok_or_else(|| "generate did not return a string".to_string())
+}
+
+proptest! {
+    #[test]
+    fn test_matching(
+        pattern in prop::sample::select(vec![
+            "[0-9]{3}-[0-9]{4}",
+            "[A-Z][a-z]+( [A-Z][a-z]+)?",
+            r"\d{5}(-\d{4})?",
+            "(?:foo|bar)+baz",
+            r"[^@\s]+@[^@\s]+\.[a-z]{2,3}",
+            "#?[0-9a-fA-F]{6}",
+            ".{2,}",
+            "x*?y{2,3}",
+            r"a\rb\f[\v\t]",
+            r"\0x\cJ\cm",
+        ]),
+        random_bytes in prop::collection::vec(any::<u8>(), 4096),
+    ) {
+        let (mut context, exports_obj) = load_random_module(random_bytes)
+            .map_err(TestCaseError::fail)?;
+        let value = generate_matching(&mut context, &exports_obj, pattern)
+            .map_err(TestCaseError::fail)?;
+        let is_match = context
+            .eval(Source::from_bytes(&format!(
+                "new RegExp({}, 'u').test({})",
+                serde_json::to_string(&format!("^(?:{pattern})$")).unwrap(),
+                serde_json::to_string(&value).unwrap(),
+            )))
+            .map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert!(is_match.to_boolean(), "{value:?} doesn't match /{pattern}/");
+    }
+}
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 
 use crate::specification::js::{
     BombadilExports, Extractors, RuntimeFunction, syntax_from_value,
@@ -17,6 +18,8 @@ use boa_engine::{JsError, JsObject, JsValue};
 use bombadil_ltl::eval::{self, Evaluator, Residual};
 use bombadil_ltl::formula::Formula;
 use bombadil_ltl::syntax::Syntax;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use serde_json as json;
 
 use crate::specification::domain::{BombadilDomain, Snapshot, UniqueSnapshots};
@@ -31,13 +34,29 @@ pub struct StepResult<A> {
 pub struct Verifier {
     context: Context,
     bombadil_exports: BombadilExports,
-    properties: HashMap<String, Property>,
-    action_generators: HashMap<String, ActionGenerator>,
+    properties: BTreeMap<String, Property>,
+    action_generators: BTreeMap<String, ActionGenerator>,
+    setup: Option<Setup<json::Value>>,
     extractors: Extractors,
 }
 
+/// Steps run from the origin before exploring, as exported by the
+/// specification with `setup`.
+#[derive(Debug, Clone)]
+pub enum Setup<A> {
+    Actions(Vec<A>),
+    /// An async function, run in the page by `BrowserAction::RunSetupScript`.
+    Script,
+}
+
 const RANDOM_BYTES_COUNT_MAX: usize = 4096;
 
+thread_local! {
+    /// Backs `__bombadil_random_bytes`. Every verifier lives on its own thread
+    /// (see `VerifierWorker`), and reseeds this in `Verifier::new`.
+    static RANDOM: RefCell<ChaCha8Rng> = RefCell::new(ChaCha8Rng::seed_from_u64(0));
+}
+
 #[derive(Clone)]
 pub struct Specification {
     pub module_specifier: String,
@@ -54,7 +73,9 @@ fn format_console_args(args: &[JsValue]) -> String {
 }
 
 impl Verifier {
-    pub fn new(bundle_code: &str) -> Result<Self> {
+    pub fn new(bundle_code: &str, seed: u64) -> Result<Self> {
+        RANDOM.with(|rng| *rng.borrow_mut() = ChaCha8Rng::seed_from_u64(seed));
+
         let mut context = ContextBuilder::default()
             .build()
             .map_err(|error| SpecificationError::JS(error.to_string()))?;
@@ -76,7 +97,7 @@ impl Verifier {
                     )));
                 }
                 let mut buf = vec![0u8; n];
-                rand::fill(&mut buf[..]);
+                RANDOM.with(|rng| rng.borrow_mut().fill_bytes(&mut buf[..]));
                 Ok(JsUint8Array::from_iter(buf, context)?.into())
             }),
         )?;
@@ -161,9 +182,10 @@ impl Verifier {
         let specification_export_keys =
             specification_exports_obj.own_property_keys(&mut context)?;
 
-        let mut properties: HashMap<String, Property> = HashMap::new();
-        let mut action_generators: HashMap<String, ActionGenerator> =
-            HashMap::new();
+        let mut properties: BTreeMap<String, Property> = BTreeMap::new();
+        let mut action_generators: BTreeMap<String, ActionGenerator> =
+            BTreeMap::new();
+        let mut setup: Option<Setup<json::Value>> = None;
         for key in specification_export_keys {
             let value =
                 specification_exports_obj.get(key.clone(), &mut context)?;
@@ -205,6 +227,42 @@ impl Verifier {
                         function,
                     },
                 );
+            } else if value
+                .instance_of(&bombadil_exports.setup, &mut context)?
+            {
+                if setup.is_some() {
+                    return Err(SpecificationError::OtherError(format!(
+                        "export {:?} is a second setup, only one is allowed",
+                        key.to_string()
+                    )));
+                }
+                let steps = value
+                    .as_object()
+                    .ok_or(SpecificationError::OtherError(format!(
+                        "setup {} is not an object, it is {}",
+                        key,
+                        value.type_of()
+                    )))?
+                    .get(js_string!("steps"), &mut context)?;
+                setup = Some(if steps.is_callable() {
+                    Setup::Script
+                } else {
+                    let steps_json = steps.to_json(&mut context)?.ok_or(
+                        SpecificationError::OtherError(format!(
+                            "setup {} has no steps",
+                            key
+                        )),
+                    )?;
+                    Setup::Actions(json::from_value(steps_json).map_err(
+                        |error| {
+                            SpecificationError::OtherError(format!(
+                                "setup {} is neither a function nor an array \
+                                 of actions: {}",
+                                key, error
+                            ))
+                        },
+                    )?)
+                });
             } else if let PropertyKey::Symbol(ref symbol) = key
                 && let Some(description) = symbol.description()
                 && IGNORED_SYMBOL_EXPORTS.contains(&description)
@@ -257,6 +315,7 @@ impl Verifier {
             context,
             properties,
             action_generators,
+            setup,
             bombadil_exports,
             extractors,
         })
@@ -266,6 +325,10 @@ impl Verifier {
         self.properties.keys().cloned().collect()
     }
 
+    pub fn setup(&self) -> Option<&Setup<json::Value>> {
+        self.setup.as_ref()
+    }
+
     pub fn step<A: serde::de::DeserializeOwned>(
         &mut self,
         snapshots: &[Snapshot],
@@ -439,6 +502,10 @@ mod tests {
     }
 
     fn verifier(specification: &str) -> Verifier {
+        verifier_with_seed(specification, 0)
+    }
+
+    fn verifier_with_seed(specification: &str, seed: u64) -> Verifier {
         use crate::specification::bundler::bundle;
 
         let mut specification_file = NamedTempFile::with_suffix(".ts").unwrap();
@@ -454,7 +521,7 @@ mod tests {
             ))
             .unwrap();
 
-        Verifier::new(&bundle_code).unwrap()
+        Verifier::new(&bundle_code, seed).unwrap()
     }
 
     #[test]
@@ -478,6 +545,69 @@ mod tests {
         assert_eq!(verifier.properties(), vec!["max_notifications_shown"]);
     }
 
+    #[test]
+    fn test_setup_actions() {
+        let verifier = verifier(
+            r##"
+            import { actions, setup } from "@antithesishq/bombadil";
+            export const _actions = actions(() => ["Wait"]);
+            export const login = setup([
+              { SetInputValue: { selector: "#user", value: "alice" } },
+              { PressKey: { code: 13 } },
+            ]);
+            "##,
+        );
+        match verifier.setup() {
+            Some(Setup::Actions(actions)) => assert_eq!(
+                actions,
+                &vec![
+                    json::json!({
+                        "SetInputValue": { "selector": "#user", "value": "alice" }
+                    }),
+                    json::json!({ "PressKey": { "code": 13 } }),
+                ]
+            ),
+            other => panic!("expected setup actions, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_setup_script() {
+        let verifier = verifier(
+            r#"
+            import { actions, setup } from "@antithesishq/bombadil";
+            export const _actions = actions(() => ["Wait"]);
+            export const login = setup(async ({ document }) => {
+              document.querySelector("form")?.submit();
+            });
+            "#,
+        );
+        assert!(matches!(verifier.setup(), Some(Setup::Script)));
+    }
+
+    #[test]
+    fn test_actions_are_reproducible_from_seed() {
+        let specification = r#"
+            import { actions, strings } from "@antithesishq/bombadil";
+            export const typing = actions(() => [
+              { TypeText: { text: strings().generate(), delayMillis: 1 } },
+            ]);
+            export const waiting = actions(() => ["Wait"]);
+            "#;
+        let time = time_from_millis(0);
+        let step = |seed: u64| {
+            let mut verifier = verifier_with_seed(specification, seed);
+            (0..10)
+                .map(|_| {
+                    verifier.step::<json::Value>(&[], time).unwrap().actions
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(step(42), step(42));
+        assert_ne!(step(42), step(43));
+    }
+
     #[test]
     fn test_property_evaluation_not() {
         let mut verifier = verifier(
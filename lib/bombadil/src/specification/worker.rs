@@ -13,13 +13,16 @@ use crate::specification::convert::{
 use crate::specification::domain::{BombadilDomain, Snapshot};
 use crate::specification::js::RuntimeFunction;
 use crate::specification::result::SpecificationError;
-use crate::specification::verifier::{Specification, Verifier};
+use crate::specification::verifier::{Setup, Specification, Verifier};
 use crate::tree::Tree;
 
 enum Command {
     GetProperties {
         reply: oneshot::Sender<Vec<String>>,
     },
+    GetSetup {
+        reply: oneshot::Sender<Option<Setup<json::Value>>>,
+    },
     Step {
         snapshots: Arc<[Snapshot]>,
         time: Time,
@@ -65,11 +68,13 @@ pub struct VerifierWorker {
 }
 
 impl VerifierWorker {
-    /// Starts the worker on its own OS thread and returns a handle.
+    /// Starts the worker on its own OS thread and returns a handle. The
+    /// specification's random generators are seeded with `seed`.
     ///
     /// Call this once at startup and share the handle as needed.
     pub async fn start(
         specification: Specification,
+        seed: u64,
     ) -> Result<Arc<Self>, SpecificationError> {
         use crate::specification::bundler::bundle;
 
@@ -91,7 +96,7 @@ impl VerifierWorker {
         let _worker_thread = std::thread::Builder::new()
             .stack_size(16 * 1024 * 1024) // 16MB stack to avoid overflows
             .spawn(move || {
-                let mut verifier = match Verifier::new(&bundle_code) {
+                let mut verifier = match Verifier::new(&bundle_code, seed) {
                     Ok(verifier) => {
                         let _ = ready_tx.send(Ok(()));
                         verifier
@@ -106,6 +111,9 @@ impl VerifierWorker {
                         Command::GetProperties { reply } => {
                             let _ = reply.send(verifier.properties());
                         }
+                        Command::GetSetup { reply } => {
+                            let _ = reply.send(verifier.setup().cloned());
+                        }
                         Command::Step {
                             snapshots,
                             time,
@@ -152,6 +160,36 @@ impl VerifierWorker {
         reply_rx.await.map_err(|_| WorkerError::WorkerGone)
     }
 
+    pub async fn setup<A: DeserializeOwned>(
+        &self,
+    ) -> Result<Option<Setup<A>>, WorkerError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(Command::GetSetup { reply: reply_tx })
+            .await
+            .map_err(|_| WorkerError::WorkerGone)?;
+        let setup = reply_rx.await.map_err(|_| WorkerError::WorkerGone)?;
+        Ok(match setup {
+            Some(Setup::Actions(actions)) => Some(Setup::Actions(
+                actions
+                    .into_iter()
+                    .map(|action| {
+                        json::from_value(action).map_err(|e| {
+                            WorkerError::SpecificationError(
+                                SpecificationError::OtherError(format!(
+                                    "failed to deserialize setup action: {}",
+                                    e
+                                )),
+                            )
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            Some(Setup::Script) => Some(Setup::Script),
+            None => None,
+        })
+    }
+
     pub async fn step<A: DeserializeOwned>(
         &self,
         snapshots: Arc<[Snapshot]>,
@@ -5,15 +5,21 @@ use serde::Serialize;
 use url::Url;
 
 use crate::{
-    browser::{actions::BrowserAction, state::Resources},
+    browser::{
+        actions::BrowserAction,
+        state::{NetworkRequest, Resources, Storage},
+    },
     specification::{convert::ToSchema, domain::Snapshot},
 };
 
+pub mod reader;
 pub mod writer;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct TraceEntry<'a> {
     pub timestamp: SystemTime,
+    pub episode: u64,
+    pub setup: bool,
     pub url: Cow<'a, Url>,
     pub hash_previous: Option<u64>,
     pub hash_current: Option<u64>,
@@ -21,6 +27,8 @@ pub struct TraceEntry<'a> {
     pub screenshot: Cow<'a, Path>,
     pub snapshots: Cow<'a, [Snapshot]>,
     pub violations: Cow<'a, [PropertyViolation]>,
+    pub network: Cow<'a, [NetworkRequest]>,
+    pub storage: Cow<'a, Storage>,
     pub resources: Cow<'a, Resources>,
 }
 
@@ -43,6 +51,8 @@ impl<'a> ToSchema<bombadil_schema::TraceEntry> for TraceEntry<'a> {
     fn to_schema(&self) -> bombadil_schema::TraceEntry {
         bombadil_schema::TraceEntry {
             timestamp: Time::from_system_time(self.timestamp),
+            episode: self.episode,
+            setup: self.setup,
             url: self.url.to_string(),
             hash_previous: self.hash_previous,
             hash_current: self.hash_current,
@@ -50,6 +60,8 @@ impl<'a> ToSchema<bombadil_schema::TraceEntry> for TraceEntry<'a> {
             screenshot: self.screenshot.to_string_lossy().to_string(),
             snapshots: self.snapshots.iter().map(|s| s.to_schema()).collect(),
             violations: self.violations.iter().map(|v| v.to_schema()).collect(),
+            network: self.network.iter().map(|r| r.to_api()).collect(),
+            storage: self.storage.to_api(),
             resources: self.resources.to_api(),
         }
     }
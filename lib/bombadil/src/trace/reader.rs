@@ -0,0 +1,35 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use bombadil_schema::TraceEntry;
+use serde_json as json;
+
+/// Resolves a path to a trace file, or to a directory containing one.
+pub fn trace_file_path(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        path.join("trace.jsonl")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Reads all entries of a trace file, or of a directory containing one.
+pub async fn read_trace(path: &Path) -> Result<Vec<TraceEntry>> {
+    let trace_file = trace_file_path(path);
+    let content =
+        tokio::fs::read_to_string(&trace_file)
+            .await
+            .with_context(|| {
+                format!("failed to read trace file {}", trace_file.display())
+            })?;
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            json::from_str(line).with_context(|| {
+                format!("failed to parse trace entry at line {}", index + 1)
+            })
+        })
+        .collect()
+}
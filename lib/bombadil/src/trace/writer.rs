@@ -7,18 +7,35 @@ use tokio::{fs::File, io::AsyncWriteExt};
 
 use crate::{
     browser::{actions::BrowserAction, state::BrowserState},
+    corpus::write_atomically,
+    graph::StateGraph,
     specification::convert::ToSchema,
     trace::{PropertyViolation, TraceEntry},
+    violations::ViolationLog,
 };
 
+/// Number of entries after which the state graph is written, even if it has
+/// no new states, to keep its visit counts and transitions up to date.
+const ENTRIES_PER_GRAPH_WRITE: u64 = 50;
+
 pub struct TraceWriter {
+    root_path: PathBuf,
     screenshots_path: PathBuf,
     trace_file: File,
     last_transition_hash: Option<u64>,
+    episode: u64,
+    setup: bool,
+    /// Number of entries written so far.
+    entries: u64,
+    graph: StateGraph,
+    violations: ViolationLog,
 }
 
 impl TraceWriter {
-    pub async fn initialize(root_path: PathBuf) -> Result<Self> {
+    pub async fn initialize(
+        root_path: PathBuf,
+        metadata: &bombadil_schema::TraceMetadata,
+    ) -> Result<Self> {
         log::info!(
             "storing trace in {}",
             &root_path
@@ -27,17 +44,33 @@ impl TraceWriter {
         );
         let screenshots_path = root_path.join("screenshots");
         tokio::fs::create_dir_all(&screenshots_path).await?;
+        tokio::fs::write(
+            root_path.join("metadata.json"),
+            json::to_vec_pretty(metadata)?,
+        )
+        .await?;
+        // Truncated rather than appended to when the output path is reused,
+        // as entries are referred to by their index, from the start of this
+        // test's trace.
         let trace_file = File::options()
-            .append(true)
+            .write(true)
             .create(true)
+            .truncate(true)
             .open(root_path.join("trace.jsonl"))
             .await?;
         Ok(TraceWriter {
+            root_path,
             screenshots_path,
             trace_file,
             last_transition_hash: None,
+            episode: 0,
+            setup: false,
+            entries: 0,
+            graph: StateGraph::new(),
+            violations: ViolationLog::new(),
         })
     }
+
     pub async fn write(
         &mut self,
         state: &BrowserState,
@@ -57,6 +90,8 @@ impl TraceWriter {
 
         let entry = TraceEntry {
             timestamp: state.timestamp,
+            episode: self.episode,
+            setup: self.setup,
             url: Cow::Borrowed(&state.url),
             hash_previous: self.last_transition_hash,
             hash_current: state.transition_hash,
@@ -64,16 +99,77 @@ impl TraceWriter {
             screenshot: Cow::Owned(screenshot_path),
             snapshots: Cow::Borrowed(snapshots),
             violations: Cow::Borrowed(violations),
+            network: Cow::Borrowed(&state.network_requests),
+            storage: Cow::Borrowed(&state.storage),
             resources: Cow::Borrowed(&state.resources),
         };
 
         self.last_transition_hash = state.transition_hash;
 
+        let entry = entry.to_schema();
+        let new_state = self.graph.record(&entry);
+        for violation in &entry.violations {
+            self.violations.record(self.entries, violation);
+        }
+
         self.trace_file
-            .write_all(json::to_string(&entry.to_schema())?.as_bytes())
+            .write_all(json::to_string(&entry)?.as_bytes())
             .await?;
         self.trace_file.write_u8(b'\n').await?;
+        self.entries += 1;
+
+        // Kept up to date as the test goes, so that there's a graph even if
+        // the test is killed.
+        if new_state || self.entries % ENTRIES_PER_GRAPH_WRITE == 0 {
+            self.write_graph().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Marks the following entries as belonging to a new episode, which
+    /// doesn't continue from the last state of the previous one.
+    pub fn start_episode(&mut self, episode: u64) {
+        self.episode = episode;
+        self.setup = false;
+        self.last_transition_hash = None;
+    }
+
+    /// Marks the following entries as reached by steps of the
+    /// specification's setup, or not.
+    pub fn set_setup(&mut self, setup: bool) {
+        self.setup = setup;
+    }
+
+    /// The distinct violations written so far.
+    pub fn violations(&self) -> &ViolationLog {
+        &self.violations
+    }
+
+    /// Writes the distinct violations of the trace so far, with their
+    /// counts, as `violations.json`.
+    pub async fn write_violations(&self) -> Result<()> {
+        tokio::fs::write(
+            self.root_path.join("violations.json"),
+            json::to_vec_pretty(&self.violations.to_schema())?,
+        )
+        .await?;
+        Ok(())
+    }
 
+    /// Writes the state graph of the trace so far, as `graph.json` and
+    /// `graph.dot`, replacing the previous ones at once.
+    pub async fn write_graph(&self) -> Result<()> {
+        write_atomically(
+            &self.root_path.join("graph.json"),
+            &json::to_vec_pretty(&self.graph.to_schema())?,
+        )
+        .await?;
+        write_atomically(
+            &self.root_path.join("graph.dot"),
+            self.graph.to_dot().as_bytes(),
+        )
+        .await?;
         Ok(())
     }
 }
@@ -70,6 +70,61 @@ impl<T> Tree<T> {
         }
     }
 
+    /// Scales the probability of picking each leaf by its `boost` factor.
+    ///
+    /// Weights are adjusted at every level of the tree, so that a boosted leaf
+    /// deep inside a branch also makes that branch more likely to be picked.
+    /// Branches are re-quantized into the `Weight` range, keeping every
+    /// non-zero weight non-zero.
+    pub fn reweight(self, boost: &impl Fn(&T) -> f64) -> Self {
+        self.reweight_with_mean(boost).0
+    }
+
+    fn reweight_with_mean(self, boost: &impl Fn(&T) -> f64) -> (Self, f64) {
+        const RESOLUTION: f64 = 1000.0;
+        match self {
+            Tree::Leaf { value } => {
+                let factor = boost(&value).max(0.0);
+                (Tree::Leaf { value }, factor)
+            }
+            Tree::Branch { branches } => {
+                let scaled: Vec<(Weight, f64, Tree<T>)> = branches
+                    .into_iter()
+                    .map(|(weight, subtree)| {
+                        let (subtree, mean) = subtree.reweight_with_mean(boost);
+                        (weight, weight as f64 * mean, subtree)
+                    })
+                    .collect();
+                let total: f64 = scaled.iter().map(|(w, _, _)| *w as f64).sum();
+                let total_scaled: f64 = scaled.iter().map(|(_, s, _)| s).sum();
+                let max_scaled =
+                    scaled.iter().map(|(_, s, _)| *s).fold(0.0f64, f64::max);
+                let branches = scaled
+                    .into_iter()
+                    .map(|(weight, scaled, subtree)| {
+                        let weight = if weight == 0 {
+                            0
+                        } else if max_scaled <= 0.0 {
+                            1
+                        } else {
+                            (scaled / max_scaled * RESOLUTION)
+                                .round()
+                                .clamp(1.0, RESOLUTION)
+                                as Weight
+                        };
+                        (weight, subtree)
+                    })
+                    .collect();
+                let mean = if total > 0.0 {
+                    total_scaled / total
+                } else {
+                    0.0
+                };
+                (Tree::Branch { branches }, mean)
+            }
+        }
+    }
+
     pub fn pick(&self, rng: &mut impl Rng) -> Result<&T> {
         match self {
             Tree::Leaf { value } => Ok(value),
@@ -209,6 +264,72 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_reweight_leaves() {
+        let tree = Branch {
+            branches: vec![(1, Leaf { value: 1 }), (1, Leaf { value: 9 })],
+        };
+        let actual = tree.reweight(&|x| *x as f64);
+        let expected = Branch {
+            branches: vec![(111, Leaf { value: 1 }), (1000, Leaf { value: 9 })],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_reweight_propagates_to_parents() {
+        let tree = Branch {
+            branches: vec![
+                (1, Leaf { value: 1 }),
+                (
+                    1,
+                    Branch {
+                        branches: vec![
+                            (1, Leaf { value: 4 }),
+                            (1, Leaf { value: 1 }),
+                        ],
+                    },
+                ),
+            ],
+        };
+        let actual = tree.reweight(&|x| *x as f64);
+        let expected = Branch {
+            branches: vec![
+                (400, Leaf { value: 1 }),
+                (
+                    1000,
+                    Branch {
+                        branches: vec![
+                            (1000, Leaf { value: 4 }),
+                            (250, Leaf { value: 1 }),
+                        ],
+                    },
+                ),
+            ],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_reweight_keeps_zero_and_nonzero_weights() {
+        let tree = Branch {
+            branches: vec![
+                (0, Leaf { value: 1000 }),
+                (1, Leaf { value: 1 }),
+                (1, Leaf { value: 1_000_000 }),
+            ],
+        };
+        let actual = tree.reweight(&|x| *x as f64);
+        let expected = Branch {
+            branches: vec![
+                (0, Leaf { value: 1000 }),
+                (1, Leaf { value: 1 }),
+                (1000, Leaf { value: 1_000_000 }),
+            ],
+        };
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_pick_single_leaf() {
         let tree = Leaf { value: 42 };
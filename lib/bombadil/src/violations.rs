@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use bombadil_schema::{
+    EventuallyViolation, Formula, PropertyViolation, UniqueViolation, Violation,
+};
+use serde_json as json;
+
+use crate::specification::convert::ToSchema;
+
+/// Identifies a violation by its property and its shape: the failing
+/// conditions and formulas, and the extracted values, but not the times at
+/// which it happened or the numbers in the values known to vary between
+/// occurrences (like timestamps, durations and ids), so that repeated
+/// failures of the same kind share a fingerprint.
+pub fn fingerprint(violation: &PropertyViolation) -> String {
+    let shape = json::json!({
+        "name": violation.name,
+        "violation": shape(&violation.violation),
+    });
+    format!("{:016x}", fnv1a(shape.to_string().as_bytes()))
+}
+
+/// The 64-bit FNV-1a hash, which unlike `DefaultHasher` is the same across
+/// Rust releases, for hashes written to disk like fingerprints in
+/// violations.json and corpus file names.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn shape(violation: &Violation) -> json::Value {
+    match violation {
+        Violation::False {
+            condition,
+            snapshots,
+            ..
+        } => json::json!({
+            "false": condition,
+            "snapshots": snapshots
+                .iter()
+                .map(|snapshot| json::json!([
+                    snapshot.name,
+                    match &snapshot.name {
+                        Some(name) => normalized(name, &snapshot.value),
+                        None => without_varying(&snapshot.value),
+                    }
+                ]))
+                .collect::<Vec<_>>(),
+        }),
+        Violation::Eventually { subformula, reason } => json::json!({
+            "eventually": formula(subformula),
+            "reason": match reason {
+                EventuallyViolation::TimedOut(_) => "timed out",
+                EventuallyViolation::TestEnded => "test ended",
+            },
+        }),
+        Violation::Always {
+            violation,
+            subformula,
+            ..
+        } => json::json!({
+            "always": formula(subformula),
+            "violation": shape(violation),
+        }),
+        Violation::And { left, right } => {
+            json::json!({ "and": [shape(left), shape(right)] })
+        }
+        Violation::Or { left, right } => {
+            json::json!({ "or": [shape(left), shape(right)] })
+        }
+        Violation::Implies { left, right, .. } => json::json!({
+            "implies": formula(left),
+            "violation": shape(right),
+        }),
+    }
+}
+
+fn formula(formula: &Formula) -> json::Value {
+    json::to_value(formula).unwrap_or_default()
+}
+
+/// Removes the numbers of the value named by the given key, if it's known
+/// to vary, and those of its fields known to vary.
+fn normalized(key: &str, value: &json::Value) -> json::Value {
+    match value {
+        json::Value::Number(_) if is_varying(key) => json::Value::Null,
+        value => without_varying(value),
+    }
+}
+
+fn without_varying(value: &json::Value) -> json::Value {
+    match value {
+        json::Value::Array(values) => {
+            values.iter().map(without_varying).collect()
+        }
+        json::Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| (key.clone(), normalized(key, value)))
+            .collect(),
+        value => value.clone(),
+    }
+}
+
+/// Whether numbers named by the given key, like `timestamp`,
+/// `durationMillis` or `requestId`, are times or ids.
+fn is_varying(key: &str) -> bool {
+    let lowercase = key.to_ascii_lowercase();
+    lowercase.contains("time")
+        || lowercase.ends_with("millis")
+        || lowercase == "id"
+        || lowercase.ends_with("_id")
+        || key.ends_with("Id")
+}
+
+/// The distinct violations in a trace, in order of first occurrence.
+#[derive(Debug, Clone, Default)]
+pub struct ViolationLog {
+    unique: Vec<UniqueViolation>,
+    indices: HashMap<String, usize>,
+}
+
+impl ViolationLog {
+    pub fn new() -> Self {
+        ViolationLog::default()
+    }
+
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.indices.contains_key(fingerprint)
+    }
+
+    /// Adds a violation found in the trace entry with the given index.
+    pub fn record(&mut self, entry_index: u64, violation: &PropertyViolation) {
+        let fingerprint = fingerprint(violation);
+        match self.indices.get(&fingerprint) {
+            Some(index) => {
+                let unique = &mut self.unique[*index];
+                unique.count += 1;
+                unique.last_index = entry_index;
+            }
+            None => {
+                self.indices.insert(fingerprint.clone(), self.unique.len());
+                self.unique.push(UniqueViolation {
+                    fingerprint,
+                    name: violation.name.clone(),
+                    count: 1,
+                    first_index: entry_index,
+                    last_index: entry_index,
+                    violation: violation.violation.clone(),
+                });
+            }
+        }
+    }
+
+    pub fn unique(&self) -> &[UniqueViolation] {
+        &self.unique
+    }
+}
+
+impl ToSchema<Vec<UniqueViolation>> for ViolationLog {
+    fn to_schema(&self) -> Vec<UniqueViolation> {
+        self.unique.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bombadil_schema::{Snapshot, Time};
+
+    fn violation(
+        name: &str,
+        condition: &str,
+        micros: u64,
+        value: json::Value,
+    ) -> PropertyViolation {
+        let time = Time::from_system_time(
+            std::time::UNIX_EPOCH + std::time::Duration::from_micros(micros),
+        );
+        PropertyViolation {
+            name: name.to_string(),
+            violation: Violation::False {
+                time,
+                condition: condition.to_string(),
+                snapshots: vec![Snapshot {
+                    index: 0,
+                    name: Some("value".to_string()),
+                    value,
+                    time,
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_times_and_ids() {
+        let first = violation(
+            "request",
+            "() => request.current.ok",
+            1,
+            json::json!({
+                "requestId": 12,
+                "timestamp": 1000.0,
+                "durationMillis": 35,
+                "status": 500,
+            }),
+        );
+        let second = violation(
+            "request",
+            "() => request.current.ok",
+            2,
+            json::json!({
+                "requestId": 13,
+                "timestamp": 2000.0,
+                "durationMillis": 40,
+                "status": 500,
+            }),
+        );
+        assert_eq!(fingerprint(&first), fingerprint(&second));
+    }
+
+    #[test]
+    fn test_fingerprint_keeps_other_numbers() {
+        let not_found = violation(
+            "request",
+            "() => request.current.ok",
+            1,
+            json::json!({ "status": 404 }),
+        );
+        let server_error = violation(
+            "request",
+            "() => request.current.ok",
+            1,
+            json::json!({ "status": 500 }),
+        );
+        assert_ne!(fingerprint(&not_found), fingerprint(&server_error));
+        let first = violation("count", "() => x", 1, json::json!([1, 2]));
+        let second = violation("count", "() => x", 1, json::json!([1, 3]));
+        assert_ne!(fingerprint(&first), fingerprint(&second));
+    }
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_shapes() {
+        let base = violation("a", "() => x", 1, json::json!("Uncaught foo"));
+        let other_name =
+            violation("b", "() => x", 1, json::json!("Uncaught foo"));
+        let other_condition =
+            violation("a", "() => y", 1, json::json!("Uncaught foo"));
+        let other_text =
+            violation("a", "() => x", 1, json::json!("Uncaught bar"));
+        assert_ne!(fingerprint(&base), fingerprint(&other_name));
+        assert_ne!(fingerprint(&base), fingerprint(&other_condition));
+        assert_ne!(fingerprint(&base), fingerprint(&other_text));
+    }
+
+    #[test]
+    fn test_log_counts_occurrences() {
+        let mut log = ViolationLog::new();
+        log.record(2, &violation("a", "() => x", 1, json::json!(1)));
+        log.record(5, &violation("b", "() => y", 2, json::json!(1)));
+        log.record(7, &violation("a", "() => x", 3, json::json!(2)));
+
+        let unique = log.to_schema();
+        assert_eq!(unique.len(), 2);
+        assert_eq!((unique[0].name.as_str(), unique[0].count), ("a", 2));
+        assert_eq!((unique[0].first_index, unique[0].last_index), (2, 7));
+        assert_eq!((unique[1].first_index, unique[1].last_index), (5, 5));
+        assert!(log.contains(&unique[1].fingerprint));
+    }
+}
@@ -23,8 +23,10 @@ use bombadil::{
     browser::{
         Browser, BrowserOptions, DebuggerOptions, Emulation, LaunchOptions,
         actions::BrowserAction,
+        network_conditions::Throttling,
+        storage_state::{SeedCookie, SeedItem, SeedOrigin, StorageSeed},
     },
-    runner::Runner,
+    runner::{EpisodeOptions, Runner, RunnerOptions},
     specification::{convert::ToSchema, verifier::Specification},
     styled,
 };
@@ -74,6 +76,12 @@ struct BrowserIntegrationTest<'a> {
     specification: Option<&'a str>,
     grant_permissions: Vec<String>,
     extra_headers: HashMap<String, String>,
+    episodes: EpisodeOptions,
+    virtual_time: bool,
+    mobile: bool,
+    throttling: Option<Throttling>,
+    storage_seed: Option<fn(&Url) -> StorageSeed>,
+    accessibility_tree: bool,
 }
 
 impl<'a> BrowserIntegrationTest<'a> {
@@ -85,6 +93,12 @@ impl<'a> BrowserIntegrationTest<'a> {
             specification: None,
             grant_permissions: vec![],
             extra_headers: HashMap::new(),
+            episodes: EpisodeOptions::default(),
+            virtual_time: false,
+            mobile: false,
+            throttling: None,
+            storage_seed: None,
+            accessibility_tree: false,
         }
     }
 
@@ -113,6 +127,40 @@ impl<'a> BrowserIntegrationTest<'a> {
         self
     }
 
+    fn episodes(mut self, length: u64, count: u64) -> Self {
+        self.episodes = EpisodeOptions {
+            length: Some(length),
+            count: Some(count),
+        };
+        self
+    }
+
+    fn virtual_time(mut self) -> Self {
+        self.virtual_time = true;
+        self
+    }
+
+    fn mobile(mut self) -> Self {
+        self.mobile = true;
+        self
+    }
+
+    fn throttling(mut self, throttling: Throttling) -> Self {
+        self.throttling = Some(throttling);
+        self
+    }
+
+    /// Seeds storage with what the function returns for the test's origin.
+    fn storage_seed(mut self, storage_seed: fn(&Url) -> StorageSeed) -> Self {
+        self.storage_seed = Some(storage_seed);
+        self
+    }
+
+    fn accessibility_tree(mut self) -> Self {
+        self.accessibility_tree = true;
+        self
+    }
+
     /// Run a named browser test with a given expectation.
     ///
     /// Spins up two web servers: one on a random port P, and one on port P + 1, in order to
@@ -131,6 +179,12 @@ impl<'a> BrowserIntegrationTest<'a> {
             specification,
             grant_permissions,
             extra_headers,
+            episodes,
+            virtual_time,
+            mobile,
+            throttling,
+            storage_seed,
+            accessibility_tree,
         } = self;
         setup();
         let _permit = TEST_SEMAPHORE.acquire().await.unwrap();
@@ -229,20 +283,31 @@ impl<'a> BrowserIntegrationTest<'a> {
         };
 
         let downloads_directory = TempDir::new().unwrap();
+        let storage_seed = storage_seed.map(|seed| seed(&origin));
         let runner = Runner::new(
             origin,
             specification,
+            RunnerOptions {
+                seed: rand::random(),
+                exploration: Default::default(),
+                episodes,
+            },
             BrowserOptions {
                 create_target: true,
                 emulation: Emulation {
                     width: 800,
                     height: 600,
                     device_scale_factor: 2.0,
+                    mobile,
                 },
                 instrumentation: Default::default(),
                 downloads_directory: downloads_directory.path().to_path_buf(),
                 grant_permissions,
                 extra_headers,
+                virtual_time,
+                throttling,
+                storage_seed,
+                accessibility_tree,
             },
             DebuggerOptions::Managed {
                 launch_options: LaunchOptions {
@@ -297,8 +362,10 @@ impl<'a> BrowserIntegrationTest<'a> {
                     return Ok(bombadil::runner::ControlFlow::Stop(()));
                 }
 
+                // State timestamps are virtual under virtual time, but the
+                // time limit is on the wall clock.
                 if let Some(deadline) = self.deadline
-                    && state.timestamp >= deadline
+                    && SystemTime::now() >= deadline
                 {
                     log::info!("time limit reached, stopping");
                     return Ok(bombadil::runner::ControlFlow::Stop(()));
@@ -490,11 +557,16 @@ async fn test_browser_lifecycle() {
                 width: 800,
                 height: 600,
                 device_scale_factor: 2.0,
+                mobile: false,
             },
             instrumentation: Default::default(),
             downloads_directory: downloads_directory.path().to_path_buf(),
             grant_permissions: vec![],
             extra_headers: Default::default(),
+            virtual_time: false,
+            throttling: None,
+            storage_seed: None,
+            accessibility_tree: false,
         },
         DebuggerOptions::Managed {
             launch_options: LaunchOptions {
@@ -513,6 +585,9 @@ async fn test_browser_lifecycle() {
         bombadil::browser::BrowserEvent::StateChanged(state) => {
             assert_eq!(state.title, "Console Error");
         }
+        bombadil::browser::BrowserEvent::ActionFailed(action, error) => {
+            panic!("unexpected failure of {:?}: {}", action, error)
+        }
         bombadil::browser::BrowserEvent::Error(error) => {
             panic!("unexpected browser error: {}", error)
         }
@@ -526,6 +601,9 @@ async fn test_browser_lifecycle() {
         bombadil::browser::BrowserEvent::StateChanged(state) => {
             assert_eq!(state.title, "Console Error");
         }
+        bombadil::browser::BrowserEvent::ActionFailed(action, error) => {
+            panic!("unexpected failure of {:?}: {}", action, error)
+        }
         bombadil::browser::BrowserEvent::Error(error) => {
             panic!("unexpected browser error: {}", error)
         }
@@ -923,3 +1001,665 @@ export const dialogWasAccepted = now(
         .run()
         .await;
 }
+
+#[tokio::test]
+async fn test_episodes() {
+    BrowserIntegrationTest::new("episodes")
+        .episodes(3, 4)
+        .specification(
+            r##"
+import { extract, always } from "@antithesishq/bombadil";
+export { clicks } from "@antithesishq/bombadil/defaults/actions";
+
+const count = extract((state) => {
+  const element = state.document.querySelector("#count");
+  return element ? Number(element.textContent) : 0;
+});
+
+export const countIsResetEachEpisode = always(() => count.current <= 3);
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_network_requests() {
+    BrowserIntegrationTest::new("network-requests")
+        .time_limit(Duration::from_secs(15))
+        .specification(
+            r#"
+import { extract, always, eventually } from "@antithesishq/bombadil";
+export { clicks } from "@antithesishq/bombadil/defaults/actions";
+
+const missingStatuses = extract((state) =>
+  state.network
+    .filter((request) => request.url.endsWith("/missing.json"))
+    .map((request) => request.status),
+);
+
+export const missingIsNotFound = always(() =>
+  missingStatuses.current.every((status) => status === 404),
+);
+
+export const missingWasRequested = eventually(
+  () => missingStatuses.current.length > 0,
+).within(10, "seconds");
+"#,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_network_faults() {
+    BrowserIntegrationTest::new("network-faults")
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { actions, extract, eventually } from "@antithesishq/bombadil";
+export { clicks } from "@antithesishq/bombadil/defaults/actions";
+
+const status = extract((state) => {
+  const element = state.document.querySelector("#status");
+  return element ? element.textContent : "";
+});
+
+export const serverErrors = actions(() => [
+  {
+    InjectFault: {
+      urlPattern: "*/data.json",
+      fault: { Status: { code: 503 } },
+    },
+  },
+]);
+
+export const serverErrorIsShown = eventually(
+  () => status.current === "error 503",
+).within(20, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_virtual_time() {
+    BrowserIntegrationTest::new("virtual-time")
+        .virtual_time()
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { actions, extract, eventually } from "@antithesishq/bombadil";
+
+const session = extract(
+  (state) => state.document.querySelector("#session")?.textContent ?? "",
+);
+
+export const advanceClock = actions(() => [
+  { AdvanceClock: { millis: 60000 } },
+]);
+
+export const sessionExpires = eventually(
+  () => session.current === "expired",
+).within(15, "minutes");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_advance_clock_without_virtual_time() {
+    BrowserIntegrationTest::new("virtual-time")
+        .expect_error("AdvanceClock actions require virtual time")
+        .specification(
+            r##"
+import { actions, always } from "@antithesishq/bombadil";
+
+export const advanceClock = actions(() => [
+  { AdvanceClock: { millis: 60000 } },
+]);
+
+export const neverDone = always(() => true);
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_virtual_time_limit() {
+    // Advancing the virtual clock by hours doesn't use up the time limit.
+    let start = std::time::Instant::now();
+    BrowserIntegrationTest::new("virtual-time-limit")
+        .virtual_time()
+        .time_limit(Duration::from_secs(5))
+        .specification(
+            r##"
+import { actions, always } from "@antithesishq/bombadil";
+
+export const advanceClock = actions(() => [
+  { AdvanceClock: { millis: 3_600_000 } },
+]);
+
+export const neverDone = always(() => true);
+"##,
+        )
+        .run()
+        .await;
+    assert!(
+        start.elapsed() >= Duration::from_secs(5),
+        "test stopped after {:?}, before its time limit",
+        start.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn test_touch() {
+    BrowserIntegrationTest::new("touch")
+        .mobile()
+        .time_limit(Duration::from_secs(20))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export { clicks } from "@antithesishq/bombadil/defaults/actions";
+
+const status = extract(
+  (state) => state.document.querySelector("#status")?.textContent ?? "",
+);
+
+export const buttonIsTapped = eventually(
+  () => status.current === "tapped",
+).within(10, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_touch_gestures() {
+    BrowserIntegrationTest::new("touch")
+        .mobile()
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export {
+  clicks,
+  longPresses,
+  pinches,
+} from "@antithesishq/bombadil/defaults/actions";
+
+const lastAction = extract((state) => state.lastAction);
+
+function applied(name: string) {
+  return eventually(
+    () =>
+      typeof lastAction.current === "object" &&
+      lastAction.current !== null &&
+      name in lastAction.current,
+  ).within(20, "seconds");
+}
+
+export const tapped = applied("Tap");
+export const longPressed = applied("LongPress");
+export const pinched = applied("Pinch");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_drag() {
+    BrowserIntegrationTest::new("drag")
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export { drags } from "@antithesishq/bombadil/defaults/actions";
+
+const value = extract(
+  (state) => state.document.querySelector("#value")?.textContent ?? "",
+);
+
+const status = extract(
+  (state) => state.document.querySelector("#status")?.textContent ?? "",
+);
+
+export const sliderMoves = eventually(() => value.current !== "0").within(
+  20,
+  "seconds",
+);
+
+export const itemIsDropped = eventually(
+  () => status.current === "dropped item",
+).within(20, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_hover_and_context_click() {
+    BrowserIntegrationTest::new("hover-and-context-click")
+        .time_limit(Duration::from_secs(20))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export {
+  hovers,
+  contextClicks,
+} from "@antithesishq/bombadil/defaults/actions";
+
+const submenuShown = extract((state) => {
+  const submenu = state.document.querySelector("#submenu");
+  return !!submenu && state.window.getComputedStyle(submenu).display !== "none";
+});
+
+const contextMenuShown = extract(
+  (state) => state.document.querySelector("#context-menu")?.hidden === false,
+);
+
+export const submenuIsShown = eventually(() => submenuShown.current).within(
+  10,
+  "seconds",
+);
+
+export const contextMenuIsShown = eventually(
+  () => contextMenuShown.current,
+).within(10, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_form_controls() {
+    BrowserIntegrationTest::new("form-controls")
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { extract, always, eventually } from "@antithesishq/bombadil";
+export {
+  clicks,
+  inputs,
+  selects,
+  toggles,
+  inputValues,
+} from "@antithesishq/bombadil/defaults/actions";
+
+const value = (selector) =>
+  extract((state) => state.document.querySelector(selector)?.value ?? null);
+
+const size = value("#size");
+const volume = value("#volume");
+const birthday = value("#birthday");
+const favourite = value("#favourite");
+
+const agreed = extract(
+  (state) => state.document.querySelector("#agree")?.checked === true,
+);
+
+const notes = extract(
+  (state) => state.document.querySelector("#notes")?.textContent ?? "",
+);
+
+export const sizeIsSelected = eventually(() => size.current !== "").within(
+  20,
+  "seconds",
+);
+
+export const agreementIsToggled = eventually(() => agreed.current).within(
+  20,
+  "seconds",
+);
+
+export const volumeIsSet = eventually(() => volume.current !== "0").within(
+  20,
+  "seconds",
+);
+
+export const volumeIsAStep = always(() =>
+  ["0", "5", "10"].includes(volume.current),
+);
+
+export const birthdayIsSet = eventually(() => birthday.current !== "").within(
+  20,
+  "seconds",
+);
+
+export const favouriteIsSet = eventually(
+  () => favourite.current !== "#000000",
+).within(20, "seconds");
+
+export const notesAreTyped = eventually(() => notes.current !== "").within(
+  20,
+  "seconds",
+);
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_form_constraints() {
+    BrowserIntegrationTest::new("form-constraints")
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export { clicks, inputs } from "@antithesishq/bombadil/defaults/actions";
+
+const validity = (selector) =>
+  extract((state) => {
+    const input = state.document.querySelector(selector);
+    return {
+      empty: input.value === "",
+      valid: input.validity.valid,
+      patternMismatch: input.validity.patternMismatch,
+      rangeOverflow: input.validity.rangeOverflow,
+      rangeUnderflow: input.validity.rangeUnderflow,
+    };
+  });
+
+const zip = validity("#zip");
+const quantity = validity("#quantity");
+
+export const zipIsValid = eventually(() => zip.current.valid).within(
+  20,
+  "seconds",
+);
+
+export const zipMismatchesPattern = eventually(
+  () => !zip.current.empty && zip.current.patternMismatch,
+).within(20, "seconds");
+
+export const quantityIsValid = eventually(() => quantity.current.valid).within(
+  20,
+  "seconds",
+);
+
+export const quantityIsOutOfRange = eventually(
+  () => quantity.current.rangeOverflow || quantity.current.rangeUnderflow,
+).within(20, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_tabs() {
+    BrowserIntegrationTest::new("tabs")
+        .time_limit(Duration::from_secs(40))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export { clicks } from "@antithesishq/bombadil/defaults/actions";
+
+const activeTitle = extract(
+  (state) => state.tabs.find((tab) => tab.active)?.title ?? "",
+);
+
+const signedIn = extract(
+  (state) => state.document.querySelector("#status")?.textContent === "Signed in",
+);
+
+export const popupIsFollowed = eventually(
+  () => activeTitle.current === "Authorize",
+).within(20, "seconds");
+
+export const signInCompletes = eventually(() => signedIn.current).within(
+  30,
+  "seconds",
+);
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_resize() {
+    BrowserIntegrationTest::new("resize")
+        .time_limit(Duration::from_secs(20))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+import { resizesTo } from "@antithesishq/bombadil/defaults/actions";
+
+export const resizes = resizesTo([
+  { width: 375, height: 667 },
+  { width: 1280, height: 800 },
+]);
+
+const isShown = (selector) =>
+  extract((state) => {
+    const element = state.document.querySelector(selector);
+    return !!element && state.window.getComputedStyle(element).display !== "none";
+  });
+
+const menuButtonShown = isShown("#menu-button");
+const menuShown = isShown("#menu");
+
+export const narrowLayoutIsReached = eventually(
+  () => menuButtonShown.current && !menuShown.current,
+).within(10, "seconds");
+
+export const wideLayoutIsReached = eventually(
+  () => menuShown.current && !menuButtonShown.current,
+).within(10, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_connectivity() {
+    BrowserIntegrationTest::new("connectivity")
+        .time_limit(Duration::from_secs(20))
+        .throttling(Throttling::preset("fast-4g").unwrap())
+        .specification(
+            r##"
+import { extract, always, eventually, now } from "@antithesishq/bombadil";
+export { connectivity } from "@antithesishq/bombadil/defaults/actions";
+
+const online = extract((state) => state.connectivity.online);
+const latency = extract(
+  (state) => state.connectivity.throttling?.latencyMillis ?? null,
+);
+const navigatorOnline = extract((state) => state.window.navigator.onLine);
+const bannerShown = extract(
+  (state) => !!state.document.querySelector("#offline-banner:not([hidden])"),
+);
+
+export const throttlingIsReported = always(() => latency.current === 165);
+
+export const navigatorFollowsConnectivity = always(
+  () => navigatorOnline.current === online.current,
+);
+
+export const offlineBannerAppears = always(
+  now(() => !online.current).implies(
+    eventually(() => bannerShown.current).within(3, "seconds"),
+  ),
+);
+
+export const goesOffline = eventually(() => bannerShown.current).within(
+  10,
+  "seconds",
+);
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_storage_state() {
+    BrowserIntegrationTest::new("storage-state")
+        .time_limit(Duration::from_secs(20))
+        .storage_seed(|origin| StorageSeed {
+            cookies: vec![SeedCookie {
+                name: "session".to_string(),
+                value: "s3cret".to_string(),
+                url: None,
+                domain: None,
+                path: Some("/".to_string()),
+                expires: None,
+                http_only: true,
+                secure: false,
+                same_site: Some("Lax".to_string()),
+            }],
+            origins: vec![SeedOrigin {
+                origin: origin.to_string(),
+                local_storage: vec![SeedItem {
+                    name: "user".to_string(),
+                    value: "alice".to_string(),
+                }],
+            }],
+        })
+        .specification(
+            r##"
+import { extract, always, eventually } from "@antithesishq/bombadil";
+export { reload } from "@antithesishq/bombadil/defaults/actions";
+
+const storage = extract((state) => state.storage);
+const greeting = extract(
+  (state) => state.document.querySelector("#greeting")?.textContent ?? "",
+);
+
+export const neverAnonymous = always(
+  () => greeting.current !== "Hello, anonymous",
+);
+
+export const greetsSeededUser = eventually(
+  () => greeting.current === "Hello, alice",
+).within(5, "seconds");
+
+export const sessionCookieIsCaptured = eventually(() =>
+  storage.current.cookies.some(
+    (cookie) =>
+      cookie.name === "session" && cookie.value === "s3cret" && cookie.httpOnly,
+  ),
+).within(5, "seconds");
+
+export const webStorageIsCaptured = eventually(
+  () =>
+    storage.current.localStorage.user === "alice" &&
+    storage.current.sessionStorage.visited === "yes" &&
+    storage.current.indexedDbDatabases.includes("app"),
+).within(5, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_setup() {
+    BrowserIntegrationTest::new("setup")
+        .time_limit(Duration::from_secs(60))
+        .episodes(3, 3)
+        .specification(
+            r##"
+import { extract, always, eventually, setup } from "@antithesishq/bombadil";
+export { reload } from "@antithesishq/bombadil/defaults/actions";
+
+export const login = setup(async ({ document }) => {
+  document.querySelector("#username").value = "alice";
+  document.querySelector("#login").requestSubmit();
+});
+
+const greeting = extract(
+  (state) => state.document.querySelector("#greeting")?.textContent ?? "",
+);
+const lastAction = extract((state) => state.lastAction);
+
+// Episodes start logged out, and the setup logs in again before any reload.
+export const loggedInAfterSetup = always(
+  () => lastAction.current === null || greeting.current === "Hello, alice",
+);
+
+export const setupRuns = eventually(
+  () => greeting.current === "Hello, alice",
+).within(10, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_setup_failure() {
+    BrowserIntegrationTest::new("setup")
+        .time_limit(Duration::from_secs(30))
+        .expect_error("setup step RunSetupScript failed")
+        .specification(
+            r##"
+import { setup } from "@antithesishq/bombadil";
+export { reload } from "@antithesishq/bombadil/defaults/actions";
+
+export const login = setup(async ({ document }) => {
+  document.querySelector("#no-such-field").value = "alice";
+});
+"##,
+        )
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_accessibility() {
+    BrowserIntegrationTest::new("accessibility")
+        .time_limit(Duration::from_secs(30))
+        .accessibility_tree()
+        .specification(
+            r##"
+export { clicks } from "@antithesishq/bombadil/defaults/actions";
+export {
+  interactiveElementsHaveNames,
+  focusNeverOnHiddenElement,
+} from "@antithesishq/bombadil/defaults/properties";
+"##,
+        )
+        .expect_error("interactiveElementsHaveNames")
+        .run()
+        .await;
+}
+
+#[tokio::test]
+async fn test_key_chords() {
+    BrowserIntegrationTest::new("key-chords")
+        .time_limit(Duration::from_secs(30))
+        .specification(
+            r##"
+import { extract, eventually } from "@antithesishq/bombadil";
+export { shortcuts } from "@antithesishq/bombadil/defaults/actions";
+
+const text = (selector) =>
+  extract((state) => state.document.querySelector(selector)?.textContent);
+
+const undo = text("#undo");
+const backTab = text("#back-tab");
+
+export const undoIsPressed = eventually(() => undo.current === "undo").within(
+  20,
+  "seconds",
+);
+
+export const backTabIsPressed = eventually(
+  () => backTab.current === "back tab",
+).within(20, "seconds");
+"##,
+        )
+        .run()
+        .await;
+}